@@ -22,29 +22,252 @@ appearance = Appearance
 theme = Theme
 units = Units
 time-format = Time Format
+system-timezone = System Timezone
+system-timezone-description = Show times in the timezone of this computer instead of the location's. Days are still those of the location.
+locations = Locations
+provider = Weather Provider
+network = Network
+offline = Offline Mode
+offline-description = Never connect to the weather services and only show cached forecasts.
+proxy = Proxy
+proxy-description = HTTP or SOCKS5 proxy URL. Leave empty to use the system proxy variables.
+no-proxy = No Proxy For
+no-proxy-description = Comma separated hosts and domains reached directly.
+ca-certificates = Additional CA Certificates
+ca-certificates-description = Path to a PEM file with root certificates to trust.
+request-timeout = Request Timeout
+contact-email = Contact Email
+contact-email-description = Sent to the weather services so they can reach you, e.g. when running many instances.
+history = History
+archive-retention = Keep Fetched Forecasts
+archive-retention-description = Fetched forecasts are archived and can be looked up under Past Forecasts or with "cosmic-ext-forecast history".
+archive-off = Off
+archive-days = { $count ->
+    [one] one day
+   *[other] { $count } days
+}
+forecast-changes = Forecast Changes
+change-notifications = Desktop Notifications
+change-notifications-description = Notify when a refresh changes the forecast by at least the thresholds below.
+change-temperature-threshold = Temperature
+change-temperature-threshold-description = Change of a daily high or low in the selected units.
+change-precipitation-threshold = Start of Precipitation
+change-precipitation-threshold-description = Hours the expected start of rain or snow moved.
+change-wind-speed-threshold = Wind Speed
+change-wind-speed-threshold-description = Change of the strongest wind in the next 24 hours in the selected units.
+threshold-off = Off
+threshold-hours = { $count ->
+    [one] one hour
+   *[other] { $count } hours
+}
+
+## Weather Providers
+provider-met-no = Norwegian Meteorological Institute (MET Norway)
+provider-open-meteo = Open-Meteo
+provider-nws = US National Weather Service
 
 ## App Themes
 dark = Dark
 light = Light
 system = Match desktop
 
+# Attribution
+attribution-met-no = Weather data from the Norwegian Meteorological Institute
+attribution-open-meteo = Weather data by Open-Meteo.com (CC BY 4.0)
+attribution-nws = Weather data from the US National Weather Service
+
+# Weather Symbols
+symbol-unknown = Unknown
+symbol-clearsky = Clear sky
+symbol-fair = Fair
+symbol-partlycloudy = Partly cloudy
+symbol-cloudy = Cloudy
+symbol-fog = Fog
+symbol-lightrainshowers = Light rain showers
+symbol-lightrain = Light rain
+symbol-lightsleetshowers = Light sleet showers
+symbol-lightsleet = Light sleet
+symbol-lightsnowshowers = Light snow showers
+symbol-lightsnow = Light snow
+symbol-rainshowers = Rain showers
+symbol-rain = Rain
+symbol-sleetshowers = Sleet showers
+symbol-sleet = Sleet
+symbol-snowshowers = Snow showers
+symbol-snow = Snow
+symbol-heavyrainshowers = Heavy rain showers
+symbol-heavyrain = Heavy rain
+symbol-heavysleetshowers = Heavy sleet showers
+symbol-heavysleet = Heavy sleet
+symbol-heavysnowshowers = Heavy snow showers
+symbol-heavysnow = Heavy snow
+symbol-lightrainshowersandthunder = Light rain showers and thunder
+symbol-lightrainandthunder = Light rain and thunder
+symbol-lightsleetshowersandthunder = Light sleet showers and thunder
+symbol-lightsleetandthunder = Light sleet and thunder
+symbol-lightsnowshowersandthunder = Light snow showers and thunder
+symbol-lightsnowandthunder = Light snow and thunder
+symbol-rainshowersandthunder = Rain showers and thunder
+symbol-rainandthunder = Rain and thunder
+symbol-sleetshowersandthunder = Sleet showers and thunder
+symbol-sleetandthunder = Sleet and thunder
+symbol-snowshowersandthunder = Snow showers and thunder
+symbol-snowandthunder = Snow and thunder
+symbol-heavyrainshowersandthunder = Heavy rain showers and thunder
+symbol-heavyrainandthunder = Heavy rain and thunder
+symbol-heavysleetshowersandthunder = Heavy sleet showers and thunder
+symbol-heavysleetandthunder = Heavy sleet and thunder
+symbol-heavysnowshowersandthunder = Heavy snow showers and thunder
+symbol-heavysnowandthunder = Heavy snow and thunder
+
 # Dialog Page
 change-city = Change City
 save = Save
 search = Search
+add = Add
 cancel = Cancel
 dummy-dialog = Dummy Dialog
 search = Search
 
+# Details
+air-pressure = Air Pressure
+air-pressure-description = Reduced to sea level, so places at different heights compare.
+cloud-cover = Cloud Cover
+cloud-cover-description = Part of the sky covered by clouds at any height.
+cloud-cover-low = Low Clouds
+cloud-cover-low-description = Clouds below about 2 km.
+cloud-cover-medium = Medium Clouds
+cloud-cover-medium-description = Clouds between about 2 and 6 km.
+cloud-cover-high = High Clouds
+cloud-cover-high-description = Clouds above about 6 km.
+fog = Fog
+fog-description = Part of the area covered by fog.
+relative-humidity = Relative Humidity
+relative-humidity-description = Water vapor in the air compared to the most it can hold.
+dew-point = Dew Point
+dew-point-description = Temperature at which dew or fog forms as the air cools.
+wind-direction = Wind Direction
+wind-direction-description = Where the wind blows from, clockwise from north.
+wind-speed = Wind Speed
+wind-speed-description = Average over 10 minutes.
+wind-gusts = Wind Gusts
+wind-gusts-description = Strongest short bursts of wind.
+uv-index = UV Index
+uv-index-description = Sunburn risk under a clear sky. Use sun protection from 3.
+uv-index-value = { $index } ({ $level ->
+    [low] low
+    [moderate] moderate
+    [high] high
+    [very-high] very high
+   *[extreme] extreme
+})
+visibility = Visibility
+visibility-description = Distance at which objects can still be seen.
+
+# Precipitation
+precipitation-units = Precipitation Units
+precipitation-next-hours = Precipitation, next { $hours ->
+    [one] hour
+   *[other] { $hours } hours
+}
+
+# Hourly chart
+chart-temperature = Temperature
+chart-feels-like = Feels like
+chart-precipitation = Precipitation
+chart-cloud-cover = Cloud cover
+
+# Forecast age
+last-updated = Last updated: { $time }
+outdated = Outdated, fetched { $age }
+offline-outdated = Offline, fetched { $age }
+no-current-conditions = No current conditions in this forecast
+age-just-now = just now
+age-minutes = { $count ->
+    [one] one minute ago
+   *[other] { $count } minutes ago
+}
+age-hours = { $count ->
+    [one] one hour ago
+   *[other] { $count } hours ago
+}
+age-days = { $count ->
+    [one] one day ago
+   *[other] { $count } days ago
+}
+
+# Forecast changes
+forecast-changed = Forecast for { $location } changed
+change-precipitation-moved = { $category ->
+    [snow] Snow
+    [sleet] Sleet
+    [thunder] Thunderstorms
+   *[rain] Rain
+} now expected from { $time } instead of { $previous }
+change-precipitation-started = { $category ->
+    [snow] Snow
+    [sleet] Sleet
+    [thunder] Thunderstorms
+   *[rain] Rain
+} now expected from { $time }
+change-precipitation-stopped = { $category ->
+    [snow] Snow
+    [sleet] Sleet
+    [thunder] Thunderstorms
+   *[rain] Rain
+} no longer expected
+weekday = { $weekday ->
+    [monday] Monday
+    [tuesday] Tuesday
+    [wednesday] Wednesday
+    [thursday] Thursday
+    [friday] Friday
+    [saturday] Saturday
+   *[sunday] Sunday
+}
+change-high = { $day ->
+    [today] Today's high
+    [tomorrow] Tomorrow's high
+   *[other] { $weekday }'s high
+} { $direction ->
+    [up] up
+   *[down] down
+} { $difference }
+change-low = { $day ->
+    [today] Today's low
+    [tomorrow] Tomorrow's low
+   *[other] { $weekday }'s low
+} { $direction ->
+    [up] up
+   *[down] down
+} { $difference }
+change-wind-speed = Strongest wind in the next 24 hours { $direction ->
+    [up] up
+   *[down] down
+} to { $speed }
+
 # Nav Page
 hourly-forecast = Hourly Forecast
 daily-forecast = Daily Forecast
 details = Details
+compare-locations = Compare Locations
+compare-needs-locations = Add at least two locations to compare them.
+compared-locations = Compared Locations
+compared-locations-description = Up to { $count } locations are compared side by side.
+past-forecasts = Past Forecasts
+history-issued = Forecast
+history-issued-description = Issue time of the archived forecast shown.
+history-archive-off = Fetched forecasts are not kept. Choose how long to keep them in the settings.
+history-empty = No forecasts of this location have been archived yet.
 
 # Menu
 
 ## File
 file = File
+export-json = Export as JSON
+export-csv = Export as CSV
+exported = Forecast exported to { $path }
+open-export = Open
 quit = Quit
 
 ## Edit
@@ -52,3 +275,35 @@ edit = Edit
 
 ## View
 view = View
+refresh = Refresh
+retry = Retry
+
+# Command line
+cli-no-location = No location configured. Open Forecast and set a location first.
+cli-temperature = Temperature
+cli-feels-like = Feels like
+cli-humidity = Humidity
+cli-wind = Wind
+cli-pressure = Pressure
+cli-updated = Updated
+cli-time = Time
+cli-temp = Temp
+cli-precip = Precip
+cli-conditions = Conditions
+cli-day = Day
+cli-high = High
+cli-low = Low
+
+# Errors
+error-network = Could not connect to the weather service: { $error }
+error-status = The weather service answered with HTTP status { $status }.
+error-status-server = The weather service is unavailable (HTTP status { $status }).
+error-status-forbidden = The weather service refused the request. It blocks clients that do not follow its terms of service.
+error-status-too-many-requests = Too many requests were sent to the weather service. Try again later.
+error-decode = The weather service sent an unexpected response: { $error }
+error-invalid-coordinates = The saved coordinates { $latitude }, { $longitude } are not valid. Remove the location and add it again.
+error-location-not-found = No location found for "{ $query }".
+error-no-weather-data = No weather data available.
+error-io = Could not read or write files: { $error }
+error-network-settings = The network settings are invalid: { $error }
+error-offline = Offline mode is enabled.
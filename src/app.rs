@@ -1,34 +1,57 @@
-use config::{AppTheme, PressureUnits, SpeedUnits, TimeFmt, WeatherConfigState, CONFIG_VERSION};
+use chrono::{DateTime, Local, Utc};
+use config::{
+    AppTheme, ForecastState, PrecipitationUnits, PressureUnits, SavedLocation, SpeedUnits, TimeFmt,
+    WeatherConfigState, CONFIG_VERSION, MAX_COMPARED_LOCATIONS,
+};
 use cosmic::cosmic_config::Update;
 use cosmic::cosmic_theme::ThemeMode;
 use cosmic::iced::keyboard::{Key, Modifiers};
+use cosmic::prelude::CollectionWidget;
 use cosmic::widget::menu::action::MenuAction;
 use cosmic::widget::menu::key_bind::KeyBind;
 use cosmic::{
     app::{Command, Core},
     cosmic_config::{self, CosmicConfigEntry},
     cosmic_theme, executor,
-    iced::{event, keyboard::Event as KeyEvent, window, Alignment, Event, Length, Subscription},
+    iced::{
+        event, keyboard::Event as KeyEvent, time, window, Alignment, Event, Length, Subscription,
+    },
     theme, widget,
     widget::{column, container, nav_bar, scrollable},
     ApplicationExt, Apply, Element,
 };
 use std::any::TypeId;
-use std::collections::{HashMap, VecDeque};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 
 pub mod config;
 pub mod icon_cache;
 pub mod key_bind;
 pub mod localize;
 pub mod menu;
+pub mod refresh;
 pub mod settings;
 
 use crate::app::config::{Units, WeatherConfig};
 use crate::app::icon_cache::icon_cache_get;
 use crate::app::key_bind::key_binds;
+use crate::app::refresh::{RefreshSchedule, REFRESH_TICK_SECONDS};
 use crate::fl;
+use crate::model::archive::{self, RETENTION_DAYS};
+use crate::model::cache;
+use crate::model::changes::{
+    self, ForecastChange, PRECIPITATION_THRESHOLDS, TEMPERATURE_THRESHOLDS, WIND_SPEED_THRESHOLDS,
+};
+use crate::model::error::ForecastError;
+use crate::model::export::{self, export, ExportFormat};
+use crate::model::http;
 use crate::model::location::Location;
+use crate::model::provider::Provider;
 use crate::model::weather::{WeatherData, WeatherRequestStatus};
+use crate::views::history::History;
+
+/// Request timeouts in seconds selectable in the settings.
+const REQUEST_TIMEOUTS: &[u64] = &[10, 30, 60, 120];
 
 #[derive(Clone, Debug)]
 pub enum Message {
@@ -42,16 +65,49 @@ pub enum Message {
     Config(WeatherConfig),
     Units(Units),
     TimeFmt(TimeFmt),
+    SystemTimezone(bool),
     PressureUnits(PressureUnits),
+    PrecipitationUnits(PrecipitationUnits),
     SpeedUnits(SpeedUnits),
     AppTheme(AppTheme),
+    Provider(Provider),
+    ContactEmail(String),
+    Proxy(String),
+    NoProxy(String),
+    CaCertificates(String),
+    RequestTimeout(u64),
+    Offline(bool),
+    ApplyNetworkSettings,
+    ArchiveRetention(u32),
+    TemperatureThreshold(u32),
+    PrecipitationThreshold(u32),
+    WindSpeedThreshold(u32),
+    ChangeNotifications(bool),
     DialogComplete(String),
     DialogCancel,
     DialogUpdate(DialogPage),
     UpdateLocations(Vec<Location>),
     SetLocation(Location),
-    SetWeatherData((WeatherConfigState, WeatherRequestStatus)),
-    Error(String),
+    AddLocation(Location),
+    SelectLocation(usize),
+    RemoveLocation(usize),
+    CompareLocation(usize, bool),
+    CacheLoaded(Provider, String, Option<WeatherData>),
+    SetWeatherData(Provider, String, (ForecastState, WeatherRequestStatus)),
+    WeatherDataError(Provider, String, ForecastError),
+    Refresh,
+    DismissError,
+    DismissChanges,
+    Tick(DateTime<Utc>),
+    NetworkAvailable,
+    Export(ExportFormat),
+    Exported(PathBuf),
+    DismissExported,
+    ArchivePruned,
+    HistoryLoaded(String, Vec<DateTime<Utc>>),
+    SelectPastForecast(usize),
+    PastForecastLoaded(String, DateTime<Utc>, Option<WeatherData>),
+    Error(ForecastError),
 }
 
 #[derive(Clone, Debug)]
@@ -87,6 +143,9 @@ pub enum Action {
     About,
     Settings,
     ChangeCity,
+    Refresh,
+    ExportJson,
+    ExportCsv,
     Quit,
 }
 
@@ -98,6 +157,9 @@ impl MenuAction for Action {
             Action::About => Message::ToggleContextPage(ContextPage::About),
             Action::Settings => Message::ToggleContextPage(ContextPage::Settings),
             Action::ChangeCity => Message::ChangeCity,
+            Action::Refresh => Message::Refresh,
+            Action::ExportJson => Message::Export(ExportFormat::Json),
+            Action::ExportCsv => Message::Export(ExportFormat::Csv),
             Action::Quit => Message::Quit,
         }
     }
@@ -108,11 +170,19 @@ pub enum NavPage {
     HourlyView,
     DailyView,
     Details,
+    Compare,
+    History,
 }
 
 impl NavPage {
     fn all() -> &'static [Self] {
-        &[Self::HourlyView, Self::DailyView, Self::Details]
+        &[
+            Self::HourlyView,
+            Self::DailyView,
+            Self::Details,
+            Self::Compare,
+            Self::History,
+        ]
     }
 
     fn title(&self) -> String {
@@ -120,6 +190,8 @@ impl NavPage {
             Self::HourlyView => fl!("hourly-forecast"),
             Self::DailyView => fl!("daily-forecast"),
             Self::Details => fl!("details"),
+            Self::Compare => fl!("compare-locations"),
+            Self::History => fl!("past-forecasts"),
         }
     }
 
@@ -128,6 +200,8 @@ impl NavPage {
             Self::HourlyView => icon_cache_get("view-hourly", 16),
             Self::DailyView => icon_cache_get("view-daily", 16),
             Self::Details => icon_cache_get("view-detail", 16),
+            Self::Compare => icon_cache_get("view-dual-symbolic", 16),
+            Self::History => icon_cache_get("document-open-recent-symbolic", 16),
         }
     }
 }
@@ -142,15 +216,36 @@ pub struct App {
     config_state_handler: Option<cosmic_config::Config>,
     pub config: WeatherConfig,
     pub config_state: WeatherConfigState,
+    /// Archived forecasts of the displayed location, for the history page.
+    pub history: History,
 
     app_locations: Vec<Location>,
     units: Vec<String>,
     timefmt: Vec<String>,
     pressure_units: Vec<String>,
+    precipitation_units: Vec<String>,
     speed_units: Vec<String>,
     app_themes: Vec<String>,
+    providers: Vec<String>,
+    request_timeouts: Vec<String>,
+    archive_retentions: Vec<String>,
+    temperature_thresholds: Vec<String>,
+    precipitation_thresholds: Vec<String>,
+    wind_speed_thresholds: Vec<String>,
     dialog_pages: VecDeque<DialogPage>,
     dialog_page_text: widget::Id,
+    refresh_schedule: RefreshSchedule,
+    /// Keys of the locations with a forecast request in flight.
+    loading: HashSet<String>,
+    /// Last error, shown in a banner until dismissed or a request succeeds.
+    error: Option<ForecastError>,
+    /// Error applying the network settings, shown in the settings.
+    network_error: Option<ForecastError>,
+    /// A location and how its last refresh changed the forecast, shown in a
+    /// banner until dismissed.
+    changes: Option<(SavedLocation, Vec<ForecastChange>)>,
+    /// Path of the last export, shown in a banner until dismissed.
+    exported: Option<PathBuf>,
 }
 
 impl cosmic::Application for App {
@@ -191,8 +286,10 @@ impl cosmic::Application for App {
             "kPa".to_string(),
             "psi".to_string(),
         ];
+        let app_precipitation_units = vec!["mm".to_string(), "in".to_string()];
         let app_speed_units = vec!["m/s".to_string(), "mph".to_string(), "km/h".to_string()];
         let app_themes = vec![fl!("light"), fl!("dark"), fl!("system")];
+        let app_providers = Provider::all().iter().map(Provider::name).collect();
 
         let mut app = App {
             core,
@@ -206,43 +303,87 @@ impl cosmic::Application for App {
             units: app_units,
             timefmt: app_timefmt,
             pressure_units: app_pressure_units,
+            precipitation_units: app_precipitation_units,
             speed_units: app_speed_units,
             app_themes,
+            providers: app_providers,
+            request_timeouts: REQUEST_TIMEOUTS
+                .iter()
+                .map(|timeout| format!("{timeout} s"))
+                .collect(),
+            archive_retentions: RETENTION_DAYS
+                .iter()
+                .map(|&days| match days {
+                    0 => fl!("archive-off"),
+                    days => fl!("archive-days", count = days),
+                })
+                .collect(),
+            temperature_thresholds: TEMPERATURE_THRESHOLDS
+                .iter()
+                .map(|&degrees| match degrees {
+                    0 => fl!("threshold-off"),
+                    degrees => format!("{degrees}°"),
+                })
+                .collect(),
+            precipitation_thresholds: PRECIPITATION_THRESHOLDS
+                .iter()
+                .map(|&hours| match hours {
+                    0 => fl!("threshold-off"),
+                    hours => fl!("threshold-hours", count = hours),
+                })
+                .collect(),
+            wind_speed_thresholds: WIND_SPEED_THRESHOLDS
+                .iter()
+                .map(|&speed| match speed {
+                    0 => fl!("threshold-off"),
+                    speed => speed.to_string(),
+                })
+                .collect(),
             dialog_pages: VecDeque::new(),
             dialog_page_text: widget::Id::unique(),
             config_state: flags.config_state,
             config_state_handler: flags.config_state_handler,
+            refresh_schedule: RefreshSchedule::default(),
+            history: History::default(),
+            loading: HashSet::new(),
+            error: None,
+            network_error: None,
+            changes: None,
+            exported: None,
         };
 
         // Default location to Denver if empty
         // TODO: Default to user location
-        if app.config.location.is_none() {
+        if app.config.locations.is_empty() {
             let command = Command::perform(
                 Location::get_location_data(String::from("Denver")),
                 |data| match data {
                     Ok(data) => {
                         let Some(data) = data.first() else {
                             return cosmic::app::Message::App(Message::Error(
-                                "Could not get location data.".to_string(),
+                                ForecastError::LocationNotFound(String::from("Denver")),
                             ));
                         };
                         cosmic::app::Message::App(Message::SetLocation(data.clone()))
                     }
-                    Err(err) => cosmic::app::Message::App(Message::Error(err.to_string())),
+                    Err(err) => cosmic::app::Message::App(Message::Error(err)),
                 },
             );
 
             commands.push(command);
         }
 
+        app.configure_http();
+
         // Do not open nav bar by default
         app.core.nav_bar_set_toggled(false);
 
-        if app.config_state.expires.is_none()
-            || app.config_state.expires <= Some(chrono::offset::Utc::now().into())
-        {
-            commands.push(app.update_weather_data());
+        // Forecasts are read in the background so the window opens right away
+        for location in app.config.locations.clone() {
+            commands.push(app.load_cached_weather_data(&location));
         }
+        commands.push(app.update_expired_weather_data());
+        commands.push(Self::prune_archive(app.config.archive_retention_days));
         commands.push(app.update_title());
 
         (app, Command::batch(commands))
@@ -285,10 +426,19 @@ impl cosmic::Application for App {
 
                     for location in self.app_locations.iter() {
                         locations_btns = locations_btns.push(
-                            widget::button(location.as_ref())
-                                .width(Length::Fill)
-                                .on_press(Message::SetLocation(location.clone()))
-                                .style(theme::Button::Link),
+                            widget::row()
+                                .align_items(Alignment::Center)
+                                .spacing(space_xxs)
+                                .push(
+                                    widget::button(location.as_ref())
+                                        .width(Length::Fill)
+                                        .on_press(Message::SetLocation(location.clone()))
+                                        .style(theme::Button::Link),
+                                )
+                                .push(
+                                    widget::button::standard(fl!("add"))
+                                        .on_press(Message::AddLocation(location.clone())),
+                                ),
                         );
                     }
 
@@ -316,15 +466,64 @@ impl cosmic::Application for App {
         vec![menu::menu_bar(&self.key_binds)]
     }
 
+    fn header_end(&self) -> Vec<Element<Self::Message>> {
+        let mut elements = vec![];
+
+        if self.config.locations.len() >= 2 {
+            elements.push(
+                widget::dropdown(
+                    &self.config.locations,
+                    Some(self.config.selected_location),
+                    Message::SelectLocation,
+                )
+                .into(),
+            );
+        }
+
+        if self.config.offline {
+            elements.push(
+                widget::icon::from_name("network-offline-symbolic")
+                    .size(16)
+                    .icon()
+                    .into(),
+            );
+        } else if self.loading.is_empty() {
+            elements.push(
+                widget::button::icon(widget::icon::from_name("view-refresh-symbolic"))
+                    .on_press(Message::Refresh)
+                    .into(),
+            );
+        } else {
+            elements.push(
+                widget::icon::from_name("process-working-symbolic")
+                    .size(16)
+                    .icon()
+                    .into(),
+            );
+        }
+
+        elements
+    }
+
     fn on_nav_select(&mut self, id: nav_bar::Id) -> Command<Message> {
         self.nav_model.activate(id);
 
-        Command::none()
+        match self.nav_model.active_data::<NavPage>() {
+            Some(NavPage::Compare) => Command::batch(
+                self.displayed_locations()
+                    .iter()
+                    .map(|location| self.update_expired_location_weather_data(location))
+                    .collect::<Vec<_>>(),
+            ),
+            Some(NavPage::History) => self.load_history(),
+            _ => Command::none(),
+        }
     }
 
     fn subscription(&self) -> Subscription<Self::Message> {
         struct ConfigSubscription;
         struct ThemeSubscription;
+        struct NetworkSubscription;
 
         let subscriptions = vec![
             event::listen_with(|event, status| match event {
@@ -367,6 +566,19 @@ impl cosmic::Application for App {
                 }
                 Message::SystemThemeModeChange
             }),
+            time::every(std::time::Duration::from_secs(REFRESH_TICK_SECONDS as u64))
+                .map(|_| Message::Tick(Utc::now())),
+            cosmic::iced::subscription::channel(
+                TypeId::of::<NetworkSubscription>(),
+                1,
+                |mut output| async move {
+                    // Without the portal, failed refreshes are only retried on the timer
+                    if let Err(err) = watch_network(&mut output).await {
+                        log::warn!("not watching network changes: {err}");
+                    }
+                    std::future::pending().await
+                },
+            ),
         ];
 
         Subscription::batch(subscriptions)
@@ -412,6 +624,7 @@ impl cosmic::Application for App {
                 if config != self.config {
                     log::info!("Updating config");
                     self.config = config;
+                    self.configure_http();
                 }
             }
             Message::Units(units) => {
@@ -422,10 +635,18 @@ impl cosmic::Application for App {
                 self.config.timefmt = timefmt;
                 commands.push(self.save_config());
             }
+            Message::SystemTimezone(use_system_timezone) => {
+                self.config.use_system_timezone = use_system_timezone;
+                commands.push(self.save_config());
+            }
             Message::PressureUnits(units) => {
                 self.config.pressure_units = units;
                 commands.push(self.save_config());
             }
+            Message::PrecipitationUnits(units) => {
+                self.config.precipitation_units = units;
+                commands.push(self.save_config());
+            }
             Message::SpeedUnits(speed) => {
                 self.config.speed_units = speed;
                 commands.push(self.save_config());
@@ -435,11 +656,80 @@ impl cosmic::Application for App {
                 commands.push(self.save_config());
                 commands.push(self.save_theme());
             }
+            Message::Provider(provider) => {
+                if provider != self.config.provider {
+                    self.config.provider = provider;
+                    // Cached data and request dates belong to the previous
+                    // provider, and responses still in flight are dropped
+                    self.config_state = WeatherConfigState::default();
+                    self.loading.clear();
+                    commands.push(self.save_config());
+                    commands.push(self.save_config_state());
+                    commands.push(self.update_expired_weather_data());
+                    commands.push(self.reload_history());
+                }
+            }
+            Message::ContactEmail(contact_email) => {
+                self.config.contact_email = contact_email;
+                commands.push(self.save_config());
+            }
+            Message::Proxy(proxy) => {
+                self.config.proxy = proxy;
+                commands.push(self.save_config());
+            }
+            Message::NoProxy(no_proxy) => {
+                self.config.no_proxy = no_proxy;
+                commands.push(self.save_config());
+            }
+            Message::CaCertificates(path) => {
+                self.config.ca_certificates = path;
+                commands.push(self.save_config());
+            }
+            Message::Offline(offline) => {
+                self.config.offline = offline;
+                self.configure_http();
+                commands.push(self.save_config());
+                if !offline {
+                    for location in self.displayed_locations() {
+                        commands.push(self.update_expired_location_weather_data(&location));
+                    }
+                }
+            }
+            Message::ApplyNetworkSettings => {
+                self.configure_http();
+            }
+            Message::TemperatureThreshold(degrees) => {
+                self.config.change_thresholds.temperature = degrees;
+                commands.push(self.save_config());
+            }
+            Message::PrecipitationThreshold(hours) => {
+                self.config.change_thresholds.precipitation_hours = hours;
+                commands.push(self.save_config());
+            }
+            Message::WindSpeedThreshold(speed) => {
+                self.config.change_thresholds.wind_speed = speed;
+                commands.push(self.save_config());
+            }
+            Message::ChangeNotifications(change_notifications) => {
+                self.config.change_notifications = change_notifications;
+                commands.push(self.save_config());
+            }
+            Message::ArchiveRetention(days) => {
+                self.config.archive_retention_days = days;
+                commands.push(self.save_config());
+                commands.push(Self::prune_archive(days));
+            }
+            Message::RequestTimeout(timeout) => {
+                self.config.request_timeout = timeout;
+                self.configure_http();
+                commands.push(self.save_config());
+            }
             Message::DialogComplete(city) => {
+                self.configure_http();
                 let command =
                     Command::perform(Location::get_location_data(city), |data| match data {
                         Ok(data) => cosmic::app::Message::App(Message::UpdateLocations(data)),
-                        Err(err) => cosmic::app::Message::App(Message::Error(err.to_string())),
+                        Err(err) => cosmic::app::Message::App(Message::Error(err)),
                     });
 
                 commands.push(command);
@@ -455,28 +745,257 @@ impl cosmic::Application for App {
                 self.app_locations = locations;
             }
             Message::SetLocation(location) => {
-                self.config.location = Some(location.display_name.clone());
-                self.config.latitude = Some(location.lat.clone());
-                self.config.longitude = Some(location.lon.clone());
+                let location = SavedLocation::from(&location);
+                match self.config.locations.get_mut(self.config.selected_location) {
+                    Some(selected) => {
+                        let previous = std::mem::replace(selected, location);
+                        self.config_state.forecasts.remove(&previous.key());
+                        commands.push(self.save_config_state());
+                    }
+                    None => {
+                        self.config.locations.push(location);
+                        self.config.selected_location = self.config.locations.len() - 1;
+                    }
+                }
+                commands.push(self.save_config());
+                commands.push(self.update_expired_weather_data());
+
+                self.dialog_pages.pop_front();
+            }
+            Message::AddLocation(location) => {
+                self.config.locations.push(SavedLocation::from(&location));
+                self.config.selected_location = self.config.locations.len() - 1;
                 commands.push(self.save_config());
-                commands.push(self.update_weather_data());
+                commands.push(self.update_expired_weather_data());
 
                 self.dialog_pages.pop_front();
             }
-            Message::SetWeatherData((config_state, status)) => {
-                match status {
-                    WeatherRequestStatus::NotModified => {
-                        self.config_state.expires = config_state.expires;
-                        self.config_state.last_request = config_state.last_request;
+            Message::SelectLocation(index) => {
+                if index < self.config.locations.len() {
+                    self.config.selected_location = index;
+                    commands.push(self.save_config());
+                    commands.push(self.update_expired_weather_data());
+                    commands.push(self.reload_history());
+                }
+            }
+            Message::RemoveLocation(index) => {
+                if index < self.config.locations.len() {
+                    let location = self.config.locations.remove(index);
+                    self.config_state.forecasts.remove(&location.key());
+                    if self.config.selected_location >= index && self.config.selected_location > 0 {
+                        self.config.selected_location -= 1;
                     }
-                    WeatherRequestStatus::Other => {
-                        self.config_state = config_state;
+                    commands.push(self.save_config());
+                    commands.push(self.save_config_state());
+                    commands.push(self.update_expired_weather_data());
+                    commands.push(self.reload_history());
+                }
+            }
+            Message::CompareLocation(index, compared) => {
+                let shown: Vec<String> = self
+                    .config
+                    .compared_locations()
+                    .iter()
+                    .map(|location| location.key())
+                    .collect();
+                if compared && shown.len() >= MAX_COMPARED_LOCATIONS {
+                    return Command::none();
+                }
+                // Until locations are chosen the first ones are compared, keep
+                // them when choosing another
+                for location in &mut self.config.locations {
+                    location.compared = shown.contains(&location.key());
+                }
+                if let Some(location) = self.config.locations.get_mut(index) {
+                    location.compared = compared;
+                }
+                commands.push(self.save_config());
+                for location in self.displayed_locations() {
+                    commands.push(self.update_expired_location_weather_data(&location));
+                }
+            }
+            Message::CacheLoaded(provider, key, weather_data) => {
+                if provider != self.config.provider {
+                    return Command::none();
+                }
+                let Some(forecast_state) = self.config_state.forecasts.get_mut(&key) else {
+                    return Command::none();
+                };
+                match weather_data {
+                    // A forecast fetched in the meantime is newer
+                    Some(weather_data) => {
+                        forecast_state.weather_data.get_or_insert(weather_data);
                     }
+                    None if forecast_state.weather_data.is_none() => {
+                        // The cached forecast was evicted, fetch it again
+                        forecast_state.expires = None;
+                        let displayed = self.displayed_locations();
+                        if let Some(location) =
+                            displayed.iter().find(|location| location.key() == key)
+                        {
+                            commands.push(self.update_expired_location_weather_data(location));
+                        }
+                    }
+                    None => {}
                 }
-
-                return self.save_config_state();
             }
-            Message::Error(err) => eprintln!("Error: {}", err),
+            Message::SetWeatherData(provider, key, (forecast_state, status)) => {
+                if !self.finish_request(provider, &key) {
+                    return Command::none();
+                }
+                self.error = None;
+                if let (WeatherRequestStatus::Other, Some(weather_data)) =
+                    (&status, &forecast_state.weather_data)
+                {
+                    commands.push(self.store_weather_data(&key, weather_data.clone()));
+                    commands.push(self.report_changes(&key, weather_data));
+                }
+                self.config_state.update(key, forecast_state, status);
+                commands.push(self.save_config_state());
+            }
+            Message::WeatherDataError(provider, key, err) => {
+                if !self.finish_request(provider, &key) {
+                    return Command::none();
+                }
+                self.config_state.refresh_failed(&key);
+                return self.update(Message::Error(err));
+            }
+            Message::Refresh => {
+                // Cached request dates are still sent, so unchanged forecasts
+                // are answered with `304 Not Modified`.
+                for location in self.displayed_locations() {
+                    commands.push(self.update_weather_data(&location));
+                }
+            }
+            Message::DismissError => {
+                self.error = None;
+            }
+            Message::DismissChanges => {
+                self.changes = None;
+            }
+            Message::ArchivePruned => {
+                commands.push(self.reload_history());
+            }
+            Message::HistoryLoaded(key, mut issue_times) => {
+                if self.config.location().map(SavedLocation::key).as_ref() != Some(&key) {
+                    return Command::none();
+                }
+                issue_times.reverse();
+                let labels = issue_times
+                    .iter()
+                    .map(|issued_at| self.format_issue_time(*issued_at))
+                    .collect();
+                // Keep showing the same forecast if it is still archived
+                let shown = self
+                    .history
+                    .selected
+                    .filter(|_| self.history.key.as_ref() == Some(&key))
+                    .and_then(|index| self.history.issue_times.get(index).copied());
+                let selected = shown
+                    .and_then(|shown| issue_times.iter().position(|issued_at| *issued_at == shown))
+                    .or((!issue_times.is_empty()).then_some(0));
+                self.history = History {
+                    key: Some(key),
+                    issue_times,
+                    labels,
+                    selected: None,
+                    weather_data: None,
+                };
+                if let Some(index) = selected {
+                    return self.update(Message::SelectPastForecast(index));
+                }
+            }
+            Message::SelectPastForecast(index) => {
+                let Some(issued_at) = self.history.issue_times.get(index).copied() else {
+                    return Command::none();
+                };
+                let (Some(key), Some(location)) =
+                    (self.history.key.clone(), self.config.location())
+                else {
+                    return Command::none();
+                };
+                let Ok(coords) = location.coordinates() else {
+                    return Command::none();
+                };
+                self.history.selected = Some(index);
+                commands.push(Command::perform(
+                    archive::forecast_at(self.config.provider, coords, issued_at),
+                    move |result| match result {
+                        Ok(weather_data) => cosmic::app::Message::App(Message::PastForecastLoaded(
+                            key,
+                            issued_at,
+                            weather_data,
+                        )),
+                        Err(err) => cosmic::app::Message::App(Message::Error(err)),
+                    },
+                ));
+            }
+            Message::PastForecastLoaded(key, issued_at, weather_data) => {
+                let shown = self
+                    .history
+                    .selected
+                    .and_then(|index| self.history.issue_times.get(index));
+                if self.history.key.as_ref() == Some(&key) && shown == Some(&issued_at) {
+                    self.history.weather_data = weather_data;
+                }
+            }
+            Message::Tick(now) => {
+                let displayed = self.displayed_locations();
+                let due = self.refresh_schedule.tick(
+                    now,
+                    displayed.iter().map(|location| {
+                        let key = location.key();
+                        let forecast_state = self.config_state.forecasts.get(&key);
+                        (key, forecast_state)
+                    }),
+                );
+                for location in displayed
+                    .iter()
+                    .filter(|location| due.contains(&location.key()))
+                {
+                    commands.push(self.update_weather_data(location));
+                }
+            }
+            Message::NetworkAvailable => {
+                self.refresh_schedule.network_available();
+                return self.update(Message::Tick(Utc::now()));
+            }
+            Message::Export(format) => {
+                let Some(weather_data) = self.weather_data() else {
+                    return self.update(Message::Error(ForecastError::NoWeatherData));
+                };
+                let Some(directory) = dirs::download_dir().or_else(dirs::home_dir) else {
+                    return self.update(Message::Error(ForecastError::Io(
+                        "Could not find a directory to export to.".to_string(),
+                    )));
+                };
+                let name = format!("forecast-{}", weather_data.updated_at.format("%Y%m%d-%H%M"));
+                let contents = export(&self.config, weather_data, format);
+
+                commands.push(Command::perform(
+                    tokio::task::spawn_blocking(move || {
+                        export::write_new(&directory, &name, format.extension(), &contents)
+                    }),
+                    |result| match result {
+                        Ok(Ok(path)) => cosmic::app::Message::App(Message::Exported(path)),
+                        Ok(Err(err)) => cosmic::app::Message::App(Message::Error(err.into())),
+                        Err(err) => cosmic::app::Message::App(Message::Error(ForecastError::Io(
+                            err.to_string(),
+                        ))),
+                    },
+                ));
+            }
+            Message::Exported(path) => {
+                log::info!("exported forecast to {}", path.display());
+                self.exported = Some(path);
+            }
+            Message::DismissExported => {
+                self.exported = None;
+            }
+            Message::Error(err) => {
+                log::error!("{}", err);
+                self.error = Some(err);
+            }
             Message::SystemThemeModeChange => {
                 commands.push(self.save_theme());
                 commands.push(self.save_config());
@@ -491,11 +1010,20 @@ impl cosmic::Application for App {
             Some(NavPage::HourlyView) => self.view_hourly_forecast(),
             Some(NavPage::DailyView) => self.view_daily_forecast(),
             Some(NavPage::Details) => self.view_detail_forecast(),
+            Some(NavPage::Compare) => self.view_compare_forecast(),
+            Some(NavPage::History) => self.view_history(),
             None => cosmic::widget::text("Unkown page selected.").into(),
         };
 
         column()
             .spacing(24)
+            .push_maybe(self.error.as_ref().map(Self::error_banner))
+            .push_maybe(
+                self.changes
+                    .as_ref()
+                    .map(|(location, changes)| self.changes_banner(location, changes)),
+            )
+            .push_maybe(self.exported.as_deref().map(Self::exported_banner))
             .push(container(page_view).width(Length::Fill))
             .apply(container)
             .width(Length::Fill)
@@ -523,6 +1051,7 @@ where
         if let Some(ref config_handler) = self.config_handler {
             if let Err(err) = self.config.write_entry(config_handler) {
                 log::error!("failed to save config: {}", err);
+                self.error = Some(ForecastError::Io(err.to_string()));
             }
         }
 
@@ -532,47 +1061,373 @@ where
         if let Some(ref config_state_handler) = self.config_state_handler {
             if let Err(err) = self.config_state.write_entry(config_state_handler) {
                 log::error!("failed to save config: {}", err);
+                self.error = Some(ForecastError::Io(err.to_string()));
             }
         }
 
         Command::none()
     }
 
+    /// Apply the network settings to the shared HTTP client.
+    ///
+    /// The client is only rebuilt if the settings changed since the last call.
+    fn configure_http(&mut self) {
+        self.network_error = http::configure(self.config.http_settings()).err();
+    }
+
     fn save_theme(&self) -> Command<Message> {
         cosmic::app::command::set_theme(self.config.app_theme.theme())
     }
 
-    fn update_weather_data(&self) -> Command<Message> {
-        let last_request = self.config_state.last_request.map(|lr| lr.to_utc());
+    /// Cached forecast of the displayed location.
+    pub fn forecast_state(&self) -> Option<&ForecastState> {
+        let location = self.config.location()?;
+        self.config_state.forecasts.get(&location.key())
+    }
+
+    pub fn weather_data(&self) -> Option<&WeatherData> {
+        self.forecast_state()?.weather_data.as_ref()
+    }
+
+    /// Timezone times of the displayed location are shown in, `None` for the
+    /// system timezone.
+    pub fn timezone(&self) -> Option<chrono_tz::Tz> {
+        self.config.timezone(self.config.location()?)
+    }
+
+    /// Fetch the forecast of the displayed location unless the cached one is still valid.
+    fn update_expired_weather_data(&mut self) -> Command<Message> {
+        match self.config.location().cloned() {
+            Some(location) => self.update_expired_location_weather_data(&location),
+            None => Command::none(),
+        }
+    }
+
+    fn update_expired_location_weather_data(
+        &mut self,
+        location: &SavedLocation,
+    ) -> Command<Message> {
+        match self.config_state.forecasts.get(&location.key()) {
+            Some(forecast_state) if !forecast_state.is_expired() => Command::none(),
+            _ => self.update_weather_data(location),
+        }
+    }
 
-        let (Some(lat), Some(long)) = (
-            self.config.latitude.as_ref(),
-            self.config.longitude.as_ref(),
-        ) else {
+    /// Read the cached forecast of `location` if the config state has one.
+    fn load_cached_weather_data(&self, location: &SavedLocation) -> Command<Message> {
+        let key = location.key();
+        if !self.config_state.forecasts.contains_key(&key) {
+            return Command::none();
+        }
+        let Ok(coords) = location.coordinates() else {
             return Command::none();
         };
 
-        let coords = (
-            lat.parse::<f64>().expect("Error parsing string to f64"),
-            long.parse::<f64>().expect("Error parsing string to f64"),
-        );
+        let provider = self.config.provider;
+        Command::perform(cache::load(provider, coords), move |data| {
+            cosmic::app::Message::App(Message::CacheLoaded(provider, key, data))
+        })
+    }
+
+    /// Read the issue times of the archived forecasts of the displayed location.
+    fn load_history(&self) -> Command<Message> {
+        // Reading would create an empty archive
+        if self.config.archive_retention_days == 0 {
+            return Command::none();
+        }
+        let Some(location) = self.config.location() else {
+            return Command::none();
+        };
+        let Ok(coords) = location.coordinates() else {
+            return Command::none();
+        };
+
+        let key = location.key();
+        Command::perform(
+            archive::issue_times(self.config.provider, coords),
+            move |result| match result {
+                Ok(issue_times) => {
+                    cosmic::app::Message::App(Message::HistoryLoaded(key, issue_times))
+                }
+                Err(err) => cosmic::app::Message::App(Message::Error(err)),
+            },
+        )
+    }
+
+    /// Read the archived forecasts again if the history page is shown.
+    fn reload_history(&self) -> Command<Message> {
+        match self.nav_model.active_data::<NavPage>() {
+            Some(NavPage::History) => self.load_history(),
+            _ => Command::none(),
+        }
+    }
+
+    /// Drop archived forecasts older than `retention_days`.
+    fn prune_archive(retention_days: u32) -> Command<Message> {
+        Command::perform(archive::prune(retention_days), |result| match result {
+            Ok(()) => cosmic::app::Message::App(Message::ArchivePruned),
+            Err(err) => cosmic::app::Message::App(Message::Error(err)),
+        })
+    }
+
+    /// Write the forecast fetched for the location with `key` to the cache and
+    /// the archive.
+    fn store_weather_data(&self, key: &str, weather_data: WeatherData) -> Command<Message> {
+        let Some(coords) = self
+            .config
+            .locations
+            .iter()
+            .find(|location| location.key() == key)
+            .and_then(|location| location.coordinates().ok())
+        else {
+            return Command::none();
+        };
+
+        let on_error = |result: Result<(), ForecastError>| match result {
+            Ok(()) => cosmic::app::Message::None,
+            Err(err) => cosmic::app::Message::App(Message::Error(err)),
+        };
+        Command::batch([
+            Command::perform(
+                archive::record(
+                    self.config.provider,
+                    coords,
+                    weather_data.clone(),
+                    self.config.archive_retention_days,
+                ),
+                on_error,
+            ),
+            Command::perform(
+                cache::store(self.config.provider, coords, weather_data),
+                on_error,
+            ),
+        ])
+    }
+
+    /// Compare a newly fetched forecast with the one it replaces, showing the
+    /// changes in a banner and, if enabled, a desktop notification.
+    fn report_changes(&mut self, key: &str, weather_data: &WeatherData) -> Command<Message> {
+        let Some(previous) = self
+            .config_state
+            .forecasts
+            .get(key)
+            .and_then(|forecast_state| forecast_state.weather_data.as_ref())
+        else {
+            return Command::none();
+        };
+        let Some(location) = self
+            .config
+            .locations
+            .iter()
+            .find(|location| location.key() == key)
+        else {
+            return Command::none();
+        };
+
+        let changes = changes::compare(&self.config, previous, weather_data, Local::now());
+        if changes.is_empty() {
+            return Command::none();
+        }
+
+        let summary = fl!("forecast-changed", location = location.name.as_str());
+        let body = changes
+            .iter()
+            .map(|change| change.message(&self.config, self.config.timezone(location)))
+            .collect::<Vec<_>>()
+            .join("\n");
+        self.changes = Some((location.clone(), changes));
+        if !self.config.change_notifications {
+            return Command::none();
+        }
+
+        Command::perform(
+            // Showing a notification blocks on D-Bus
+            async move {
+                tokio::task::spawn_blocking(move || {
+                    notify_rust::Notification::new()
+                        .appname(&fl!("cosmic-ext-forecast"))
+                        .icon(Self::APP_ID)
+                        .summary(&summary)
+                        .body(&body)
+                        .show()
+                        .map(|_| ())
+                        .map_err(|err| err.to_string())
+                })
+                .await
+            },
+            |result| {
+                match result {
+                    Ok(Ok(())) => {}
+                    Ok(Err(err)) => log::error!("failed to show notification: {}", err),
+                    Err(err) => log::error!("failed to show notification: {}", err),
+                }
+                cosmic::app::Message::None
+            },
+        )
+    }
 
+    /// Locations whose forecast is visible on the active page.
+    fn displayed_locations(&self) -> Vec<SavedLocation> {
+        match self.nav_model.active_data::<NavPage>() {
+            Some(NavPage::Compare) => self
+                .config
+                .compared_locations()
+                .into_iter()
+                .cloned()
+                .collect(),
+            _ => self.config.location().cloned().into_iter().collect(),
+        }
+    }
+
+    fn update_weather_data(&mut self, location: &SavedLocation) -> Command<Message> {
+        if self.config.offline {
+            return Command::none();
+        }
+        // Network settings typed but not submitted yet
+        self.configure_http();
+
+        let key = location.key();
+        if !self.loading.insert(key.clone()) {
+            return Command::none();
+        }
+
+        let forecast_state = self.config_state.forecasts.get(&key);
+        // Without a cached forecast `304 Not Modified` would leave nothing to show
+        let last_request = forecast_state
+            .filter(|forecast_state| forecast_state.weather_data.is_some())
+            .and_then(|forecast_state| forecast_state.last_request)
+            .map(|lr| lr.to_utc());
+        let gridpoint = forecast_state.and_then(|forecast_state| forecast_state.gridpoint.clone());
+
+        let coords = match location.coordinates() {
+            Ok(coords) => coords,
+            Err(err) => {
+                self.loading.remove(&key);
+                self.error = Some(err);
+                return Command::none();
+            }
+        };
+
+        let provider = self.config.provider;
         Command::perform(
-            WeatherData::get_weather_data(coords, last_request),
-            |data| match data {
+            WeatherData::get_weather_data(provider, coords, last_request, gridpoint),
+            move |data| match data {
                 Ok(data) => {
                     let Some(data) = data else {
-                        return cosmic::app::Message::App(Message::Error(
-                            "Could not get weather data.".to_string(),
+                        return cosmic::app::Message::App(Message::WeatherDataError(
+                            provider,
+                            key,
+                            ForecastError::NoWeatherData,
                         ));
                     };
-                    cosmic::app::Message::App(Message::SetWeatherData(data.clone()))
+                    cosmic::app::Message::App(Message::SetWeatherData(provider, key, data))
+                }
+                Err(err) => {
+                    cosmic::app::Message::App(Message::WeatherDataError(provider, key, err))
                 }
-                Err(err) => cosmic::app::Message::App(Message::Error(err.to_string())),
             },
         )
     }
 
+    /// Mark the request made with `provider` for the location with `key` as
+    /// finished, returning whether its response still matches the selected
+    /// provider and saved locations.
+    ///
+    /// Responses to requests made before the provider was changed or the
+    /// location was removed are dropped, so they never reach the cache or
+    /// the archive of the current selection.
+    fn finish_request(&mut self, provider: Provider, key: &str) -> bool {
+        if provider != self.config.provider {
+            return false;
+        }
+        self.loading.remove(key);
+        self.config
+            .locations
+            .iter()
+            .any(|location| location.key() == key)
+    }
+
+    fn error_banner(error: &ForecastError) -> Element<Message> {
+        let spacing = theme::active().cosmic().spacing;
+        widget::row()
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(widget::icon::from_name("dialog-error-symbolic").size(16))
+            .push(widget::text(error.message()).width(Length::Fill))
+            .push_maybe(
+                error
+                    .is_retryable()
+                    .then(|| widget::button::standard(fl!("retry")).on_press(Message::Refresh)),
+            )
+            .push(
+                widget::button::icon(widget::icon::from_name("window-close-symbolic"))
+                    .on_press(Message::DismissError),
+            )
+            .apply(container)
+            .padding(spacing.space_s)
+            .style(theme::Container::Card)
+            .width(Length::Fill)
+            .into()
+    }
+
+    fn changes_banner(
+        &self,
+        location: &SavedLocation,
+        changes: &[ForecastChange],
+    ) -> Element<Message> {
+        let spacing = theme::active().cosmic().spacing;
+        let timezone = self.config.timezone(location);
+        let messages = changes.iter().fold(
+            widget::column()
+                .spacing(spacing.space_xxs)
+                .width(Length::Fill)
+                .push(widget::text::heading(fl!(
+                    "forecast-changed",
+                    location = location.name.as_str()
+                ))),
+            |column, change| column.push(widget::text(change.message(&self.config, timezone))),
+        );
+        widget::row()
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(widget::icon::from_name("dialog-information-symbolic").size(16))
+            .push(messages)
+            .push(
+                widget::button::icon(widget::icon::from_name("window-close-symbolic"))
+                    .on_press(Message::DismissChanges),
+            )
+            .apply(container)
+            .padding(spacing.space_s)
+            .style(theme::Container::Card)
+            .width(Length::Fill)
+            .into()
+    }
+
+    fn exported_banner(path: &Path) -> Element<Message> {
+        let spacing = theme::active().cosmic().spacing;
+        widget::row()
+            .align_items(Alignment::Center)
+            .spacing(spacing.space_s)
+            .push(widget::icon::from_name("document-save-symbolic").size(16))
+            .push(
+                widget::text(fl!("exported", path = path.display().to_string()))
+                    .width(Length::Fill),
+            )
+            .push(
+                widget::button::standard(fl!("open-export"))
+                    .on_press(Message::LaunchUrl(path.display().to_string())),
+            )
+            .push(
+                widget::button::icon(widget::icon::from_name("window-close-symbolic"))
+                    .on_press(Message::DismissExported),
+            )
+            .apply(container)
+            .padding(spacing.space_s)
+            .style(theme::Container::Card)
+            .width(Length::Fill)
+            .into()
+    }
+
     fn about(&self) -> Element<Message> {
         let spacing = theme::active().cosmic().spacing;
         let repository = "https://github.com/cosmic-utils/forecast";
@@ -622,18 +1477,87 @@ where
             PressureUnits::Psi => 3,
         };
 
+        let selected_precipitation_units = match self.config.precipitation_units {
+            PrecipitationUnits::Millimetres => 0,
+            PrecipitationUnits::Inches => 1,
+        };
+
         let selected_speed_units = match self.config.speed_units {
             SpeedUnits::MetersPerSecond => 0,
             SpeedUnits::MilesPerHour => 1,
             SpeedUnits::KilometresPerHour => 2,
         };
 
+        let selected_provider = Provider::all()
+            .iter()
+            .position(|provider| *provider == self.config.provider);
+
         let selected_theme = match self.config.app_theme {
             config::AppTheme::Light => 0,
             config::AppTheme::Dark => 1,
             config::AppTheme::System => 2,
         };
 
+        let mut network = widget::settings::view_section(fl!("network"))
+            .add(
+                widget::settings::item::builder(fl!("offline"))
+                    .description(fl!("offline-description"))
+                    .toggler(self.config.offline, Message::Offline),
+            )
+            .add(
+                widget::settings::item::builder(fl!("proxy"))
+                    .description(fl!("proxy-description"))
+                    .control(
+                        widget::text_input("socks5://proxy:1080", &self.config.proxy)
+                            .on_input(Message::Proxy)
+                            .on_submit(Message::ApplyNetworkSettings),
+                    ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("no-proxy"))
+                    .description(fl!("no-proxy-description"))
+                    .control(
+                        widget::text_input("localhost,.example.com", &self.config.no_proxy)
+                            .on_input(Message::NoProxy)
+                            .on_submit(Message::ApplyNetworkSettings),
+                    ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("ca-certificates"))
+                    .description(fl!("ca-certificates-description"))
+                    .control(
+                        widget::text_input(
+                            "/etc/ssl/certs/company.pem",
+                            &self.config.ca_certificates,
+                        )
+                        .on_input(Message::CaCertificates)
+                        .on_submit(Message::ApplyNetworkSettings),
+                    ),
+            )
+            .add(
+                widget::settings::item::builder(fl!("request-timeout")).control(widget::dropdown(
+                    &self.request_timeouts,
+                    REQUEST_TIMEOUTS
+                        .iter()
+                        .position(|timeout| *timeout == self.config.request_timeout),
+                    |index| {
+                        Message::RequestTimeout(REQUEST_TIMEOUTS.get(index).copied().unwrap_or(30))
+                    },
+                )),
+            )
+            .add(
+                widget::settings::item::builder(fl!("contact-email"))
+                    .description(fl!("contact-email-description"))
+                    .control(
+                        widget::text_input("", &self.config.contact_email)
+                            .on_input(Message::ContactEmail)
+                            .on_submit(Message::ApplyNetworkSettings),
+                    ),
+            );
+        if let Some(error) = &self.network_error {
+            network = network.add(widget::text(error.message()).style(cosmic::style::Text::Accent));
+        }
+
         widget::settings::view_column(vec![
             widget::settings::view_section(fl!("general"))
                 .add(
@@ -660,6 +1584,11 @@ where
                         },
                     )),
                 )
+                .add(
+                    widget::settings::item::builder(fl!("system-timezone"))
+                        .description(fl!("system-timezone-description"))
+                        .toggler(self.config.use_system_timezone, Message::SystemTimezone),
+                )
                 .add(
                     widget::settings::item::builder("Pressure Units".to_string()).control(
                         widget::dropdown(
@@ -676,6 +1605,20 @@ where
                         ),
                     ),
                 )
+                .add(
+                    widget::settings::item::builder(fl!("precipitation-units")).control(
+                        widget::dropdown(
+                            &self.precipitation_units,
+                            Some(selected_precipitation_units),
+                            move |index| {
+                                Message::PrecipitationUnits(match index {
+                                    1 => PrecipitationUnits::Inches,
+                                    _ => PrecipitationUnits::Millimetres,
+                                })
+                            },
+                        ),
+                    ),
+                )
                 .add(
                     widget::settings::item::builder("Speed Units".to_string()).control(
                         widget::dropdown(
@@ -691,6 +1634,108 @@ where
                         ),
                     ),
                 )
+                .add(
+                    widget::settings::item::builder(fl!("provider")).control(widget::dropdown(
+                        &self.providers,
+                        selected_provider,
+                        move |index| {
+                            Message::Provider(
+                                Provider::all().get(index).copied().unwrap_or_default(),
+                            )
+                        },
+                    )),
+                )
+                .into(),
+            self.config
+                .locations
+                .iter()
+                .enumerate()
+                .fold(
+                    widget::settings::view_section(fl!("locations")),
+                    |section, (index, location)| {
+                        section.add(
+                            widget::settings::item::builder(location.name.clone()).control(
+                                widget::button::icon(widget::icon::from_name(
+                                    "edit-delete-symbolic",
+                                ))
+                                .on_press(Message::RemoveLocation(index)),
+                            ),
+                        )
+                    },
+                )
+                .into(),
+            network.into(),
+            widget::settings::view_section(fl!("forecast-changes"))
+                .add(
+                    widget::settings::item::builder(fl!("change-notifications"))
+                        .description(fl!("change-notifications-description"))
+                        .toggler(
+                            self.config.change_notifications,
+                            Message::ChangeNotifications,
+                        ),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("change-temperature-threshold"))
+                        .description(fl!("change-temperature-threshold-description"))
+                        .control(widget::dropdown(
+                            &self.temperature_thresholds,
+                            TEMPERATURE_THRESHOLDS.iter().position(|degrees| {
+                                *degrees == self.config.change_thresholds.temperature
+                            }),
+                            |index| {
+                                Message::TemperatureThreshold(
+                                    TEMPERATURE_THRESHOLDS.get(index).copied().unwrap_or(0),
+                                )
+                            },
+                        )),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("change-precipitation-threshold"))
+                        .description(fl!("change-precipitation-threshold-description"))
+                        .control(widget::dropdown(
+                            &self.precipitation_thresholds,
+                            PRECIPITATION_THRESHOLDS.iter().position(|hours| {
+                                *hours == self.config.change_thresholds.precipitation_hours
+                            }),
+                            |index| {
+                                Message::PrecipitationThreshold(
+                                    PRECIPITATION_THRESHOLDS.get(index).copied().unwrap_or(0),
+                                )
+                            },
+                        )),
+                )
+                .add(
+                    widget::settings::item::builder(fl!("change-wind-speed-threshold"))
+                        .description(fl!("change-wind-speed-threshold-description"))
+                        .control(widget::dropdown(
+                            &self.wind_speed_thresholds,
+                            WIND_SPEED_THRESHOLDS.iter().position(|speed| {
+                                *speed == self.config.change_thresholds.wind_speed
+                            }),
+                            |index| {
+                                Message::WindSpeedThreshold(
+                                    WIND_SPEED_THRESHOLDS.get(index).copied().unwrap_or(0),
+                                )
+                            },
+                        )),
+                )
+                .into(),
+            widget::settings::view_section(fl!("history"))
+                .add(
+                    widget::settings::item::builder(fl!("archive-retention"))
+                        .description(fl!("archive-retention-description"))
+                        .control(widget::dropdown(
+                            &self.archive_retentions,
+                            RETENTION_DAYS
+                                .iter()
+                                .position(|days| *days == self.config.archive_retention_days),
+                            |index| {
+                                Message::ArchiveRetention(
+                                    RETENTION_DAYS.get(index).copied().unwrap_or(30),
+                                )
+                            },
+                        )),
+                )
                 .into(),
             widget::settings::view_section(fl!("appearance"))
                 .add(
@@ -711,3 +1756,22 @@ where
         .into()
     }
 }
+
+/// Send [`Message::NetworkAvailable`] whenever the network monitor of the XDG
+/// desktop portal reports that the network became available.
+async fn watch_network(
+    output: &mut cosmic::iced::futures::channel::mpsc::Sender<Message>,
+) -> ashpd::Result<()> {
+    use cosmic::iced::futures::{SinkExt, StreamExt};
+
+    let monitor = ashpd::desktop::network_monitor::NetworkMonitor::new().await?;
+    let mut changes = monitor.receive_changed().await?;
+    let mut available = monitor.is_available().await?;
+    while changes.next().await.is_some() {
+        let was_available = std::mem::replace(&mut available, monitor.is_available().await?);
+        if available && !was_available && output.send(Message::NetworkAvailable).await.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
@@ -1,10 +1,22 @@
 use cosmic::{
-    cosmic_config::{self, cosmic_config_derive::CosmicConfigEntry, Config, CosmicConfigEntry},
+    cosmic_config::{
+        self, cosmic_config_derive::CosmicConfigEntry, Config, ConfigGet, ConfigSet,
+        CosmicConfigEntry,
+    },
     theme, Application,
 };
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
-use crate::model::weather::WeatherData;
+use crate::model::{
+    error::ForecastError,
+    http::HttpSettings,
+    location::Location,
+    provider::{nws::Gridpoint, Provider},
+    quantity::{Precipitation, Pressure, Speed, Temperature},
+    timezone,
+    weather::{WeatherData, WeatherRequestStatus},
+};
 
 use super::App;
 
@@ -16,12 +28,52 @@ pub enum Units {
     Celsius,
 }
 
+impl Units {
+    pub fn convert(self, temperature: Temperature) -> f64 {
+        match self {
+            Self::Fahrenheit => temperature.fahrenheit(),
+            Self::Celsius => temperature.celsius(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Fahrenheit => "°F",
+            Self::Celsius => "°C",
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 pub enum TimeFmt {
     TwelveHr,
     TwentyFourHr,
 }
 
+impl TimeFmt {
+    pub fn format<Tz: chrono::TimeZone>(self, time: &chrono::DateTime<Tz>) -> String
+    where
+        Tz::Offset: std::fmt::Display,
+    {
+        match self {
+            Self::TwelveHr => time.format("%_I:%M %p").to_string(),
+            Self::TwentyFourHr => time.format("%_H:%M").to_string(),
+        }
+    }
+
+    /// Format `time` in `timezone`, or in the system timezone if `None`.
+    pub fn format_in(
+        self,
+        time: &chrono::DateTime<chrono::Local>,
+        timezone: Option<chrono_tz::Tz>,
+    ) -> String {
+        match timezone {
+            Some(timezone) => self.format(&time.with_timezone(&timezone)),
+            None => self.format(time),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
 pub enum PressureUnits {
     Hectopascal,
@@ -30,8 +82,82 @@ pub enum PressureUnits {
     Psi,
 }
 
-#[derive(Clone, CosmicConfigEntry, Debug, Deserialize, Serialize, Default)]
-pub struct WeatherConfigState {
+impl PressureUnits {
+    pub fn convert(self, pressure: Pressure) -> f64 {
+        match self {
+            Self::Hectopascal => pressure.hectopascal(),
+            Self::Bar => pressure.bar(),
+            Self::Kilopascal => pressure.kilopascal(),
+            Self::Psi => pressure.psi(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Hectopascal => "hPa",
+            Self::Bar => "bar",
+            Self::Kilopascal => "kPa",
+            Self::Psi => "psi",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
+pub enum PrecipitationUnits {
+    Millimetres,
+    Inches,
+}
+
+impl PrecipitationUnits {
+    pub fn convert(self, precipitation: Precipitation) -> f64 {
+        match self {
+            Self::Millimetres => precipitation.millimetres(),
+            Self::Inches => precipitation.inches(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Millimetres => "mm",
+            Self::Inches => "in",
+        }
+    }
+
+    /// Decimals shown, so that light rain does not round to zero.
+    pub fn precision(self) -> usize {
+        match self {
+            Self::Millimetres => 1,
+            Self::Inches => 2,
+        }
+    }
+
+    /// `amount` with the unit, or the range from `min` to `max` if the
+    /// provider reports one, e.g. "0.2–1.4 mm".
+    pub fn format(
+        self,
+        amount: Option<Precipitation>,
+        min: Option<Precipitation>,
+        max: Option<Precipitation>,
+    ) -> Option<String> {
+        let value = |precipitation| format!("{:.*}", self.precision(), self.convert(precipitation));
+        match (amount, min, max) {
+            (_, Some(min), Some(max)) if min != max => {
+                Some(format!("{}–{} {}", value(min), value(max), self.label()))
+            }
+            (Some(amount), _, _) => Some(format!("{} {}", value(amount), self.label())),
+            _ => None,
+        }
+    }
+}
+
+/// Shortest time between two automatic refreshes, even if `Expires` is earlier.
+pub const MIN_REFRESH_MINUTES: i64 = 5;
+/// Longest time between two automatic refreshes, even if `Expires` is later.
+pub const MAX_REFRESH_MINUTES: i64 = 60;
+
+/// Cached forecast of a single saved location.
+#[derive(Clone, Debug, Deserialize, Serialize, Default)]
+pub struct ForecastState {
     /// `Expires` response header of met.no request.
     ///
     /// No new request should be sent before this date.
@@ -39,16 +165,87 @@ pub struct WeatherConfigState {
     #[serde(default)]
     pub expires: Option<chrono::DateTime<chrono::FixedOffset>>,
     /// Date of the last request.
-    /// 
+    ///
     /// Used together with the `If-Modified-Since` request header.
     /// If the weather data has not changed, the response status is `304 Not Modified`.
     #[serde(default)]
     pub last_request: Option<chrono::DateTime<chrono::FixedOffset>>,
-
+    /// Forecast read from the [`cache`](crate::model::cache).
+    ///
+    /// It is too large for the config state, which only keeps the metadata.
+    #[serde(skip)]
     pub weather_data: Option<WeatherData>,
+    /// NWS forecast grid of the location.
+    ///
+    /// Resolved once per location so that refreshes only hit the forecast endpoints.
+    #[serde(default)]
+    pub gridpoint: Option<Gridpoint>,
+    /// Whether the last refresh in this session failed, e.g. without network.
+    #[serde(skip)]
+    pub refresh_failed: bool,
+}
+
+impl ForecastState {
+    pub fn is_expired(&self) -> bool {
+        self.is_expired_at(chrono::offset::Utc::now())
+    }
+
+    pub fn is_expired_at(&self, now: chrono::DateTime<chrono::Utc>) -> bool {
+        self.expires.is_none() || self.expires <= Some(now.into())
+    }
+
+    /// Time of the next automatic refresh.
+    ///
+    /// This is the `Expires` date, clamped to [`MIN_REFRESH_MINUTES`] and
+    /// [`MAX_REFRESH_MINUTES`] after the last request. `None` if there was no
+    /// request yet.
+    pub fn next_refresh(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        let last_request = self.last_request?.to_utc();
+        let expires = self
+            .expires
+            .map_or(last_request, |expires| expires.to_utc());
+        Some(expires.clamp(
+            last_request + chrono::Duration::minutes(MIN_REFRESH_MINUTES),
+            last_request + chrono::Duration::minutes(MAX_REFRESH_MINUTES),
+        ))
+    }
+}
+
+#[derive(Clone, CosmicConfigEntry, Debug, Deserialize, Serialize, Default)]
+pub struct WeatherConfigState {
+    /// Metadata of the cached forecasts, keyed by [`SavedLocation::key`].
+    pub forecasts: HashMap<String, ForecastState>,
 }
 
 impl WeatherConfigState {
+    /// Store the result of a forecast request for the location with `key`.
+    pub fn update(
+        &mut self,
+        key: String,
+        forecast_state: ForecastState,
+        status: WeatherRequestStatus,
+    ) {
+        match status {
+            WeatherRequestStatus::NotModified => {
+                let cached = self.forecasts.entry(key).or_default();
+                cached.expires = forecast_state.expires;
+                cached.last_request = forecast_state.last_request;
+                cached.refresh_failed = false;
+            }
+            WeatherRequestStatus::Other => {
+                self.forecasts.insert(key, forecast_state);
+            }
+        }
+    }
+
+    /// Mark the last refresh of the forecast for the location with `key` as
+    /// failed, until a later one succeeds.
+    pub fn refresh_failed(&mut self, key: &str) {
+        if let Some(cached) = self.forecasts.get_mut(key) {
+            cached.refresh_failed = true;
+        }
+    }
+
     pub fn config_handler() -> Option<Config> {
         Config::new_state(App::APP_ID, CONFIG_VERSION).ok()
     }
@@ -56,6 +253,7 @@ impl WeatherConfigState {
     pub fn config() -> Self {
         match Self::config_handler() {
             Some(config_handler) => {
+                Self::migrate(&config_handler);
                 Self::get_entry(&config_handler).unwrap_or_else(|(errs, config)| {
                     log::info!("errors loading config state: {:?}", errs);
 
@@ -65,6 +263,25 @@ impl WeatherConfigState {
             None => Self::default(),
         }
     }
+
+    /// Clear the forecast that state written before multiple locations were
+    /// supported kept at the top level.
+    ///
+    /// It is replaced by [`WeatherConfigState::forecasts`], but would otherwise
+    /// stay on disk.
+    fn migrate(config_handler: &Config) {
+        for key in ["weather_data", "expires", "last_request"] {
+            // Any value but `None` is a leftover, missing keys are not
+            let value = config_handler.get::<Option<serde::de::IgnoredAny>>(key);
+            if !matches!(value, Ok(Some(_))) {
+                continue;
+            }
+            log::info!("clearing old config state {}", key);
+            if let Err(err) = config_handler.set::<Option<String>>(key, None) {
+                log::error!("failed to clear {}: {}", key, err);
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq, Serialize)]
@@ -74,34 +291,230 @@ pub enum SpeedUnits {
     KilometresPerHour,
 }
 
+impl SpeedUnits {
+    pub fn convert(self, speed: Speed) -> f64 {
+        match self {
+            Self::MetersPerSecond => speed.metres_per_second(),
+            Self::MilesPerHour => speed.miles_per_hour(),
+            Self::KilometresPerHour => speed.kilometres_per_hour(),
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::MetersPerSecond => "m/s",
+            Self::MilesPerHour => "mph",
+            Self::KilometresPerHour => "km/h",
+        }
+    }
+}
+
+/// Smallest changes between two forecasts that are reported, 0 to ignore a
+/// variable.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct ChangeThresholds {
+    /// Difference of a daily high or low in the configured temperature units.
+    pub temperature: u32,
+    /// Hours the start of precipitation moved.
+    pub precipitation_hours: u32,
+    /// Difference of the strongest wind in the configured speed units.
+    pub wind_speed: u32,
+}
+
+impl Default for ChangeThresholds {
+    fn default() -> Self {
+        Self {
+            temperature: 3,
+            precipitation_hours: 2,
+            wind_speed: 5,
+        }
+    }
+}
+
+/// Most saved locations shown side by side on the compare page.
+pub const MAX_COMPARED_LOCATIONS: usize = 4;
+
+/// A location saved by the user.
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct SavedLocation {
+    pub name: String,
+    pub latitude: String,
+    pub longitude: String,
+    /// IANA timezone, e.g. `Asia/Tokyo`.
+    ///
+    /// Resolved from the coordinates once, when the location is added or
+    /// loaded from a config written before timezones were stored.
+    #[serde(default)]
+    pub timezone: Option<String>,
+    /// Shown on the compare page.
+    #[serde(default)]
+    pub compared: bool,
+}
+
+impl SavedLocation {
+    /// Key of the cached forecast in [`WeatherConfigState::forecasts`].
+    pub fn key(&self) -> String {
+        format!("{},{}", self.latitude, self.longitude)
+    }
+
+    pub fn coordinates(&self) -> Result<(f64, f64), ForecastError> {
+        let invalid = |_| ForecastError::InvalidCoordinates {
+            latitude: self.latitude.clone(),
+            longitude: self.longitude.clone(),
+        };
+        Ok((
+            self.latitude.parse().map_err(invalid)?,
+            self.longitude.parse().map_err(invalid)?,
+        ))
+    }
+
+    pub fn timezone(&self) -> Option<chrono_tz::Tz> {
+        self.timezone.as_ref()?.parse().ok()
+    }
+
+    /// Look up the timezone from the coordinates if none is stored yet.
+    ///
+    /// Returns whether it was stored.
+    fn resolve_timezone(&mut self) -> bool {
+        if self.timezone.is_some() {
+            return false;
+        }
+        self.timezone = self
+            .coordinates()
+            .ok()
+            .and_then(timezone::at)
+            .map(|timezone| timezone.name().to_string());
+        self.timezone.is_some()
+    }
+}
+
+impl AsRef<str> for SavedLocation {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl From<&Location> for SavedLocation {
+    fn from(location: &Location) -> Self {
+        let mut saved_location = Self {
+            name: location.display_name.clone(),
+            latitude: location.lat.clone(),
+            longitude: location.lon.clone(),
+            timezone: None,
+            compared: false,
+        };
+        saved_location.resolve_timezone();
+        saved_location
+    }
+}
+
 #[derive(Clone, CosmicConfigEntry, Debug, Deserialize, Eq, PartialEq, Serialize)]
 pub struct WeatherConfig {
-    pub location: Option<String>,
-    pub latitude: Option<String>,
-    pub longitude: Option<String>,
+    pub locations: Vec<SavedLocation>,
+    /// Index of the displayed location in `locations`.
+    pub selected_location: usize,
     pub units: Units,
     pub timefmt: TimeFmt,
     pub pressure_units: PressureUnits,
+    pub precipitation_units: PrecipitationUnits,
     pub speed_units: SpeedUnits,
     pub app_theme: AppTheme,
+    pub provider: Provider,
+    /// Timeout of HTTP requests in seconds.
+    pub request_timeout: u64,
+    /// Contact email sent in the User-Agent, empty for none.
+    pub contact_email: String,
+    /// Proxy URL for all requests, empty to use the environment.
+    pub proxy: String,
+    /// Comma separated hosts reached without the proxy.
+    pub no_proxy: String,
+    /// PEM file with additional root certificates, empty for none.
+    pub ca_certificates: String,
+    /// Never access the network and only show cached forecasts.
+    pub offline: bool,
+    /// Days fetched forecasts are kept in the [`Archive`](crate::model::archive::Archive),
+    /// 0 to not archive them.
+    pub archive_retention_days: u32,
+    pub change_thresholds: ChangeThresholds,
+    /// Show a desktop notification when a refresh changes the forecast.
+    pub change_notifications: bool,
+    /// Show times in the system timezone instead of the location's.
+    ///
+    /// Only times are converted, days stay those of the location's calendar
+    /// with every provider. Open-Meteo and NWS group days on their servers.
+    pub use_system_timezone: bool,
 }
 
 impl Default for WeatherConfig {
     fn default() -> Self {
         Self {
-            location: None,
-            latitude: None,
-            longitude: None,
+            locations: Vec::new(),
+            selected_location: 0,
             units: Units::Fahrenheit,
             timefmt: TimeFmt::TwelveHr,
             pressure_units: PressureUnits::Hectopascal,
+            precipitation_units: PrecipitationUnits::Millimetres,
             speed_units: SpeedUnits::MetersPerSecond,
             app_theme: AppTheme::System,
+            provider: Provider::MetNo,
+            request_timeout: 30,
+            contact_email: String::new(),
+            proxy: String::new(),
+            no_proxy: String::new(),
+            ca_certificates: String::new(),
+            offline: false,
+            archive_retention_days: 30,
+            change_thresholds: ChangeThresholds::default(),
+            change_notifications: false,
+            use_system_timezone: false,
         }
     }
 }
 
 impl WeatherConfig {
+    /// The displayed location.
+    pub fn location(&self) -> Option<&SavedLocation> {
+        self.locations.get(self.selected_location)
+    }
+
+    /// Locations shown side by side on the compare page.
+    ///
+    /// These are the locations chosen for comparison, or the first saved
+    /// locations until any are chosen, at most [`MAX_COMPARED_LOCATIONS`].
+    pub fn compared_locations(&self) -> Vec<&SavedLocation> {
+        let any_compared = self.locations.iter().any(|location| location.compared);
+        self.locations
+            .iter()
+            .filter(|location| location.compared || !any_compared)
+            .take(MAX_COMPARED_LOCATIONS)
+            .collect()
+    }
+
+    /// Timezone times of `location` are shown in, `None` for the system
+    /// timezone.
+    pub fn timezone(&self, location: &SavedLocation) -> Option<chrono_tz::Tz> {
+        if self.use_system_timezone {
+            None
+        } else {
+            location.timezone()
+        }
+    }
+
+    pub fn http_settings(&self) -> HttpSettings {
+        let non_empty = |value: &str| {
+            let value = value.trim();
+            (!value.is_empty()).then(|| value.to_string())
+        };
+        HttpSettings {
+            timeout: std::time::Duration::from_secs(self.request_timeout),
+            contact: non_empty(&self.contact_email),
+            proxy: non_empty(&self.proxy),
+            no_proxy: non_empty(&self.no_proxy),
+            ca_certificates: non_empty(&self.ca_certificates).map(Into::into),
+            offline: self.offline,
+        }
+    }
+
     pub fn config_handler() -> Option<Config> {
         Config::new(App::APP_ID, CONFIG_VERSION).ok()
     }
@@ -109,15 +522,70 @@ impl WeatherConfig {
     pub fn config() -> WeatherConfig {
         match Self::config_handler() {
             Some(config_handler) => {
-                WeatherConfig::get_entry(&config_handler).unwrap_or_else(|(errs, config)| {
-                    log::info!("errors loading config: {:?}", errs);
+                let mut config =
+                    WeatherConfig::get_entry(&config_handler).unwrap_or_else(|(errs, config)| {
+                        log::info!("errors loading config: {:?}", errs);
 
-                    config
-                })
+                        config
+                    });
+                config.migrate(&config_handler);
+                config.resolve_timezones(&config_handler);
+                config
             }
             None => WeatherConfig::default(),
         }
     }
+
+    /// Move the single location of configs written before multiple locations
+    /// were supported into `locations`.
+    ///
+    /// The old keys are cleared, so the location does not come back once the
+    /// user removed it.
+    fn migrate(&mut self, config_handler: &Config) {
+        let get = |key| config_handler.get::<Option<String>>(key).ok().flatten();
+        let (Some(name), Some(latitude), Some(longitude)) =
+            (get("location"), get("latitude"), get("longitude"))
+        else {
+            return;
+        };
+
+        if self.locations.is_empty() {
+            log::info!("migrating saved location {}", name);
+            let mut location = SavedLocation {
+                name,
+                latitude,
+                longitude,
+                timezone: None,
+                compared: false,
+            };
+            location.resolve_timezone();
+            self.locations.push(location);
+            self.selected_location = 0;
+            if let Err(err) = self.write_entry(config_handler) {
+                log::error!("failed to save migrated location: {}", err);
+                return;
+            }
+        }
+        for key in ["location", "latitude", "longitude"] {
+            if let Err(err) = config_handler.set::<Option<String>>(key, None) {
+                log::error!("failed to clear {}: {}", key, err);
+            }
+        }
+    }
+
+    /// Store the timezones of locations saved before timezones were stored,
+    /// so they are looked up only once.
+    fn resolve_timezones(&mut self, config_handler: &Config) {
+        let mut resolved = false;
+        for location in &mut self.locations {
+            resolved |= location.resolve_timezone();
+        }
+        if resolved {
+            if let Err(err) = self.write_entry(config_handler) {
+                log::error!("failed to save location timezones: {}", err);
+            }
+        }
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize)]
@@ -145,3 +613,83 @@ impl AppTheme {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(compared: &[bool]) -> WeatherConfig {
+        WeatherConfig {
+            locations: compared
+                .iter()
+                .enumerate()
+                .map(|(index, compared)| SavedLocation {
+                    name: index.to_string(),
+                    latitude: index.to_string(),
+                    longitude: "0".to_string(),
+                    timezone: None,
+                    compared: *compared,
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn compared_names(config: &WeatherConfig) -> Vec<&str> {
+        config
+            .compared_locations()
+            .into_iter()
+            .map(|location| location.name.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn compare_chosen_locations() {
+        let config = config(&[false, true, false, true, false]);
+        assert_eq!(compared_names(&config), ["1", "3"]);
+    }
+
+    #[test]
+    fn compare_first_locations_until_chosen() {
+        let config = config(&[false; 6]);
+        assert_eq!(compared_names(&config), ["0", "1", "2", "3"]);
+    }
+
+    #[test]
+    fn resolve_timezone_once() {
+        let mut location = SavedLocation {
+            name: "Tokyo".to_string(),
+            latitude: "35.6895".to_string(),
+            longitude: "139.6917".to_string(),
+            timezone: None,
+            compared: false,
+        };
+        assert!(location.resolve_timezone());
+        assert_eq!(location.timezone.as_deref(), Some("Asia/Tokyo"));
+
+        location.timezone = Some("Europe/Berlin".to_string());
+        assert!(!location.resolve_timezone());
+        assert_eq!(location.timezone(), Some(chrono_tz::Europe::Berlin));
+    }
+
+    #[test]
+    fn refresh_failed_until_next_success() {
+        let mut config_state = WeatherConfigState::default();
+        config_state.update(
+            "home".to_string(),
+            ForecastState::default(),
+            WeatherRequestStatus::Other,
+        );
+        config_state.refresh_failed("home");
+        config_state.refresh_failed("work");
+        assert!(config_state.forecasts["home"].refresh_failed);
+        assert!(!config_state.forecasts.contains_key("work"));
+
+        config_state.update(
+            "home".to_string(),
+            ForecastState::default(),
+            WeatherRequestStatus::NotModified,
+        );
+        assert!(!config_state.forecasts["home"].refresh_failed);
+    }
+}
@@ -21,7 +21,10 @@ pub fn key_binds() -> HashMap<KeyBind, Action> {
     }
 
     bind!([Ctrl], Key::Character("c".into()), ChangeCity);
+    bind!([Ctrl], Key::Character("e".into()), ExportJson);
+    bind!([Ctrl, Shift], Key::Character("E".into()), ExportCsv);
     bind!([Ctrl], Key::Character("q".into()), Quit);
+    bind!([Ctrl], Key::Character("r".into()), Refresh);
     bind!([Ctrl], Key::Character("i".into()), About);
     bind!([Ctrl], Key::Character(",".into()), Settings);
 
@@ -13,7 +13,15 @@ pub fn menu_bar<'a>(key_binds: &HashMap<KeyBind, Action>) -> Element<'a, Message
     MenuBar::new(vec![
         Tree::with_children(
             root(fl!("file")),
-            items(key_binds, vec![Item::Button(fl!("quit"), Action::Quit)]),
+            items(
+                key_binds,
+                vec![
+                    Item::Button(fl!("export-json"), Action::ExportJson),
+                    Item::Button(fl!("export-csv"), Action::ExportCsv),
+                    Item::Divider,
+                    Item::Button(fl!("quit"), Action::Quit),
+                ],
+            ),
         ),
         Tree::with_children(
             root(fl!("edit")),
@@ -27,6 +35,8 @@ pub fn menu_bar<'a>(key_binds: &HashMap<KeyBind, Action>) -> Element<'a, Message
             items(
                 key_binds,
                 vec![
+                    Item::Button(fl!("refresh"), Action::Refresh),
+                    Item::Divider,
                     Item::Button(fl!("about"), Action::About),
                     Item::Button(fl!("settings"), Action::Settings),
                 ],
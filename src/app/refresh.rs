@@ -0,0 +1,188 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::app::config::{ForecastState, MIN_REFRESH_MINUTES};
+
+/// Interval of the timer checking whether displayed forecasts need a refresh.
+pub const REFRESH_TICK_SECONDS: i64 = 60;
+
+/// Decides when forecasts are refreshed automatically.
+///
+/// The time is passed in by the caller, the refresh timer or tests, instead
+/// of read from the system clock.
+#[derive(Debug, Default)]
+pub struct RefreshSchedule {
+    /// Time of the last refresh timer tick, used to detect system resume.
+    last_tick: Option<DateTime<Utc>>,
+    /// Time of the last automatic refresh per location key, to rate limit
+    /// retries.
+    attempts: HashMap<String, DateTime<Utc>>,
+}
+
+impl RefreshSchedule {
+    /// Keys of the `forecasts` to refresh at a timer tick at `now`.
+    ///
+    /// Forecasts are refreshed when they are due, and all of them after the
+    /// system resumed from suspend.
+    pub fn tick<'a>(
+        &mut self,
+        now: DateTime<Utc>,
+        forecasts: impl IntoIterator<Item = (String, Option<&'a ForecastState>)>,
+    ) -> Vec<String> {
+        // The timer does not advance while the system is suspended, so a large
+        // jump of the wall clock between two ticks means resume
+        let resumed = self
+            .last_tick
+            .is_some_and(|last_tick| now - last_tick > Duration::seconds(3 * REFRESH_TICK_SECONDS));
+        self.last_tick = Some(now);
+
+        let due: Vec<String> = forecasts
+            .into_iter()
+            .filter(|(key, forecast_state)| resumed || self.is_due(key, *forecast_state, now))
+            .map(|(key, _)| key)
+            .collect();
+        for key in &due {
+            self.attempts.insert(key.clone(), now);
+        }
+        due
+    }
+
+    /// Forget failed attempts once the network is back, so that the next
+    /// tick retries them right away.
+    pub fn network_available(&mut self) {
+        self.attempts.clear();
+    }
+
+    /// Whether the forecast with `key` should be fetched again at `now`.
+    ///
+    /// Failed requests do not update the forecast state, so they are retried
+    /// at most every [`MIN_REFRESH_MINUTES`].
+    fn is_due(
+        &self,
+        key: &str,
+        forecast_state: Option<&ForecastState>,
+        now: DateTime<Utc>,
+    ) -> bool {
+        let min_interval = Duration::minutes(MIN_REFRESH_MINUTES);
+        if self
+            .attempts
+            .get(key)
+            .is_some_and(|attempt| now - *attempt < min_interval)
+        {
+            return false;
+        }
+
+        match forecast_state.and_then(ForecastState::next_refresh) {
+            Some(next_refresh) => next_refresh <= now,
+            None => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    /// Minutes after 12:00 on 1 June 2024.
+    fn at(minutes: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap() + Duration::minutes(minutes)
+    }
+
+    /// Forecast requested at `last_request`, expiring at `expires`.
+    fn forecast(last_request: i64, expires: i64) -> ForecastState {
+        ForecastState {
+            last_request: Some(at(last_request).into()),
+            expires: Some(at(expires).into()),
+            ..Default::default()
+        }
+    }
+
+    /// Tick every minute from `from` to `to`, returning the minutes a refresh
+    /// of `forecast_state` was started at.
+    fn run(
+        schedule: &mut RefreshSchedule,
+        forecast_state: &ForecastState,
+        from: i64,
+        to: i64,
+    ) -> Vec<i64> {
+        (from..=to)
+            .filter(|minute| {
+                !schedule
+                    .tick(at(*minute), [("home".to_string(), Some(forecast_state))])
+                    .is_empty()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn refresh_at_expires() {
+        let mut schedule = RefreshSchedule::default();
+        assert_eq!(run(&mut schedule, &forecast(0, 30), 1, 34), [30]);
+    }
+
+    #[test]
+    fn clamp_expires() {
+        let mut schedule = RefreshSchedule::default();
+        assert_eq!(run(&mut schedule, &forecast(0, 1), 1, 5), [5]);
+
+        let mut schedule = RefreshSchedule::default();
+        assert_eq!(run(&mut schedule, &forecast(0, 180), 1, 64), [60]);
+    }
+
+    #[test]
+    fn refresh_without_forecast() {
+        let mut schedule = RefreshSchedule::default();
+        assert_eq!(schedule.tick(at(0), [("home".to_string(), None)]), ["home"]);
+    }
+
+    #[test]
+    fn back_off_while_offline() {
+        // Failed requests leave the expired forecast in place
+        let mut schedule = RefreshSchedule::default();
+        assert_eq!(
+            run(&mut schedule, &forecast(0, 30), 1, 50),
+            [30, 35, 40, 45, 50]
+        );
+    }
+
+    #[test]
+    fn refresh_on_reconnect() {
+        let mut schedule = RefreshSchedule::default();
+        let expired = forecast(0, 30);
+        assert_eq!(run(&mut schedule, &expired, 30, 32), [30]);
+
+        schedule.network_available();
+
+        assert_eq!(run(&mut schedule, &expired, 33, 40), [33, 38]);
+    }
+
+    #[test]
+    fn refresh_after_resume() {
+        let mut schedule = RefreshSchedule::default();
+        let valid = forecast(0, 60);
+        assert!(run(&mut schedule, &valid, 1, 10).is_empty());
+
+        // Suspended from minute 10 to 20
+        assert_eq!(run(&mut schedule, &valid, 20, 25), [20]);
+    }
+
+    #[test]
+    fn refresh_locations_independently() {
+        let mut schedule = RefreshSchedule::default();
+        let (home, work) = (forecast(0, 10), forecast(0, 20));
+        let forecasts = || {
+            [
+                ("home".to_string(), Some(&home)),
+                ("work".to_string(), Some(&work)),
+            ]
+        };
+
+        assert!(schedule.tick(at(9), forecasts()).is_empty());
+        assert_eq!(schedule.tick(at(10), forecasts()), ["home"]);
+        assert!(schedule.tick(at(11), forecasts()).is_empty());
+        assert_eq!(schedule.tick(at(20), forecasts()), ["home", "work"]);
+    }
+}
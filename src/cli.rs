@@ -0,0 +1,444 @@
+use chrono::{DateTime, Duration, Local, NaiveDateTime, Utc};
+
+use cosmic::cosmic_config::CosmicConfigEntry;
+
+use self::status::{print_status, StatusFormat};
+use crate::app::config::{SavedLocation, WeatherConfig, WeatherConfigState};
+use crate::app::localize::localize;
+use crate::fl;
+use crate::model::archive::{self, Archive};
+use crate::model::cache;
+use crate::model::error::ForecastError;
+use crate::model::export::{export, ExportFormat};
+use crate::model::http;
+use crate::model::quantity::Temperature;
+use crate::model::weather::{DailyForecast, WeatherData, WeatherRequestStatus};
+
+mod status;
+
+const USAGE: &str = "\
+Usage: cosmic-ext-forecast [COMMAND]
+
+Without a command the graphical application is started.
+
+Commands:
+  now                 Print the current conditions
+  hourly [--hours N]  Print the hourly forecast (default: 12 hours)
+  daily [--days N]    Print the daily forecast (default: 7 days)
+  status [--format F] Print the current conditions for status bars,
+                      F is waybar (default), i3blocks (one JSON block per
+                      run) or text
+  history [--at TIME] List the archived forecasts, or print the one in effect
+                      at TIME (YYYY-MM-DD HH:MM local time or RFC 3339) as json
+  --export FORMAT     Print the forecast as json or csv in the configured units
+  help                Print this message";
+
+/// Subcommands printing the forecast of the selected location to stdout.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Command {
+    Now,
+    Hourly { hours: usize },
+    Daily { days: usize },
+    Status { format: StatusFormat },
+    Export { format: ExportFormat },
+    History { at: Option<DateTime<Utc>> },
+    Help,
+}
+
+impl Command {
+    /// Parse the command line arguments, without the program name.
+    ///
+    /// Returns `Ok(None)` when no command is given and the GUI should start.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut args = args.into_iter();
+        let Some(command) = args.next() else {
+            return Ok(None);
+        };
+
+        let mut count = |flag: &str, default: usize| -> Result<usize, String> {
+            match args.next() {
+                None => Ok(default),
+                Some(arg) if arg == flag => args
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .ok_or_else(|| format!("{flag} expects a number")),
+                Some(arg) => Err(format!("unexpected argument {arg:?}")),
+            }
+        };
+
+        let command = match command.as_str() {
+            "now" => Self::Now,
+            "hourly" => Self::Hourly {
+                hours: count("--hours", 12)?,
+            },
+            "daily" => Self::Daily {
+                days: count("--days", 7)?,
+            },
+            "status" => Self::Status {
+                format: match args.next() {
+                    None => StatusFormat::default(),
+                    Some(arg) if arg == "--format" => args
+                        .next()
+                        .ok_or_else(|| "--format expects a status format".to_string())?
+                        .parse()?,
+                    Some(arg) => return Err(format!("unexpected argument {arg:?}")),
+                },
+            },
+            "history" => Self::History {
+                at: match args.next() {
+                    None => None,
+                    Some(arg) if arg == "--at" => Some(parse_time(
+                        &args
+                            .next()
+                            .ok_or_else(|| "--at expects a time".to_string())?,
+                    )?),
+                    Some(arg) => return Err(format!("unexpected argument {arg:?}")),
+                },
+            },
+            "--export" => Self::Export {
+                format: args
+                    .next()
+                    .ok_or_else(|| "--export expects json or csv".to_string())?
+                    .parse()?,
+            },
+            "help" | "--help" | "-h" => Self::Help,
+            other => return Err(format!("unknown command {other:?}")),
+        };
+
+        match args.next() {
+            Some(arg) => Err(format!("unexpected argument {arg:?}")),
+            None => Ok(Some(command)),
+        }
+    }
+
+    pub fn run(self) -> Result<(), String> {
+        if self == Self::Help {
+            println!("{USAGE}");
+            return Ok(());
+        }
+
+        localize();
+
+        let config = WeatherConfig::config();
+        http::configure(config.http_settings()).map_err(|err| err.message())?;
+        if let Self::Status { format } = self {
+            let status = config
+                .location()
+                .ok_or_else(|| fl!("cli-no-location"))
+                .and_then(|location| {
+                    load_weather_data(&config, location)
+                        .map(|weather_data| (location, weather_data))
+                        .map_err(|err| err.message())
+                });
+            print_status(&config, status, format);
+            return Ok(());
+        }
+
+        let location = config
+            .location()
+            .cloned()
+            .ok_or_else(|| fl!("cli-no-location"))?;
+
+        if let Self::History { at } = self {
+            return print_history(&config, &location, at).map_err(|err| err.message());
+        }
+
+        let weather_data = load_weather_data(&config, &location).map_err(|err| err.message())?;
+
+        if let Self::Export { format } = self {
+            print!("{}", export(&config, &weather_data, format));
+            return Ok(());
+        }
+
+        match self {
+            Self::Now => print_now(&config, &location, &weather_data),
+            Self::Hourly { hours } => print_hourly(&config, &location, &weather_data, hours),
+            Self::Daily { days } => print_daily(&config, &weather_data, days),
+            Self::Status { .. } | Self::Export { .. } | Self::History { .. } | Self::Help => {
+                unreachable!()
+            }
+        }
+        println!("{}", config.provider.attribution());
+
+        Ok(())
+    }
+}
+
+/// Use the cached forecast of `location`, fetching a new one once it has expired.
+pub fn load_weather_data(
+    config: &WeatherConfig,
+    location: &SavedLocation,
+) -> Result<WeatherData, ForecastError> {
+    let mut config_state = WeatherConfigState::config();
+    let key = location.key();
+    let coords = location.coordinates()?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?;
+
+    let mut cached = config_state.forecasts.remove(&key).unwrap_or_default();
+    cached.weather_data = runtime.block_on(cache::load(config.provider, coords));
+
+    if (cached.is_expired() || cached.weather_data.is_none()) && !config.offline {
+        // Without a cached forecast `304 Not Modified` would leave nothing to show
+        let last_request = cached
+            .weather_data
+            .as_ref()
+            .and(cached.last_request)
+            .map(|lr| lr.to_utc());
+        let fetched = match runtime.block_on(WeatherData::get_weather_data(
+            config.provider,
+            coords,
+            last_request,
+            cached.gridpoint.clone(),
+        )) {
+            Ok(fetched) => fetched,
+            // An outdated forecast is more useful than an error, status bars
+            // mostly run without network
+            Err(err) if cached.weather_data.is_some() => {
+                log::warn!("showing cached forecast, refresh failed: {}", err);
+                cached.refresh_failed = true;
+                None
+            }
+            Err(err) => return Err(err),
+        };
+
+        if let Some((forecast_state, status)) = fetched {
+            if let (WeatherRequestStatus::Other, Some(weather_data)) =
+                (&status, &forecast_state.weather_data)
+            {
+                let store = cache::store(config.provider, coords, weather_data.clone());
+                if let Err(err) = runtime.block_on(store) {
+                    log::error!("failed to cache forecast: {}", err);
+                }
+                let record = archive::record(
+                    config.provider,
+                    coords,
+                    weather_data.clone(),
+                    config.archive_retention_days,
+                );
+                if let Err(err) = runtime.block_on(record) {
+                    log::error!("failed to archive forecast: {}", err);
+                }
+            }
+            config_state.forecasts.insert(key.clone(), cached);
+            config_state.update(key.clone(), forecast_state, status);
+            if let Some(config_state_handler) = WeatherConfigState::config_handler() {
+                if let Err(err) = config_state.write_entry(&config_state_handler) {
+                    log::error!("failed to save config: {}", err);
+                }
+            }
+            cached = config_state.forecasts.remove(&key).unwrap_or_default();
+        }
+    }
+
+    cached.weather_data.ok_or(ForecastError::NoWeatherData)
+}
+
+/// Parse `YYYY-MM-DD HH:MM` in local time or RFC 3339.
+fn parse_time(value: &str) -> Result<DateTime<Utc>, String> {
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return Ok(time.to_utc());
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M")
+        .ok()
+        .and_then(|time| time.and_local_timezone(Local).earliest())
+        .map(|time| time.to_utc())
+        .ok_or_else(|| format!("invalid time {value:?}, expected YYYY-MM-DD HH:MM"))
+}
+
+/// List the archived forecasts of `location`, or print the one in effect at `at`.
+fn print_history(
+    config: &WeatherConfig,
+    location: &SavedLocation,
+    at: Option<DateTime<Utc>>,
+) -> Result<(), ForecastError> {
+    let coords = location.coordinates()?;
+    let archive = Archive::open()?;
+
+    let Some(at) = at else {
+        for issued_at in archive.issue_times(config.provider, coords)? {
+            println!(
+                "{}",
+                issued_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+            );
+        }
+        return Ok(());
+    };
+
+    let weather_data = archive
+        .forecast_at(config.provider, coords, at)?
+        .ok_or(ForecastError::NoWeatherData)?;
+    print!("{}", export(config, &weather_data, ExportFormat::Json));
+    Ok(())
+}
+
+fn temperature(config: &WeatherConfig, value: Option<Temperature>) -> String {
+    value
+        .map(|value| format!("{:.0}{}", config.units.convert(value), config.units.label()))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn print_now(config: &WeatherConfig, location: &SavedLocation, weather_data: &WeatherData) {
+    let Some(current) = weather_data.current_at(Local::now()) else {
+        println!("{}", fl!("no-current-conditions"));
+        return;
+    };
+    let conditions = &current.conditions;
+
+    println!("{}", location.name);
+    if let Some(symbol) = current.symbol {
+        println!("{}", symbol.description());
+    }
+
+    let mut rows = vec![(
+        fl!("cli-temperature"),
+        temperature(config, conditions.air_temperature),
+    )];
+    if conditions.apparent_temperature.is_some() {
+        rows.push((
+            fl!("cli-feels-like"),
+            temperature(config, conditions.apparent_temperature),
+        ));
+    }
+    if let Some(humidity) = conditions.relative_humidity {
+        rows.push((fl!("cli-humidity"), format!("{:.0} %", humidity.0)));
+    }
+    if let Some(speed) = conditions.wind_speed {
+        let direction = conditions
+            .wind_from_direction
+            .map(|direction| format!(" ({:.0}°)", direction.0))
+            .unwrap_or_default();
+        rows.push((
+            fl!("cli-wind"),
+            format!(
+                "{:.1} {}{direction}",
+                config.speed_units.convert(speed),
+                config.speed_units.label()
+            ),
+        ));
+    }
+    if let Some(pressure) = conditions.air_pressure_at_sea_level {
+        rows.push((
+            fl!("cli-pressure"),
+            format!(
+                "{:.1} {}",
+                config.pressure_units.convert(pressure),
+                config.pressure_units.label()
+            ),
+        ));
+    }
+    rows.push((
+        fl!("cli-updated"),
+        config
+            .timefmt
+            .format_in(&weather_data.updated_at, config.timezone(location)),
+    ));
+
+    let width = rows
+        .iter()
+        .map(|(label, _)| label.chars().count())
+        .max()
+        .unwrap_or(0);
+    for (label, value) in rows {
+        println!("{label:<width$}  {value}");
+    }
+}
+
+fn print_hourly(
+    config: &WeatherConfig,
+    location: &SavedLocation,
+    weather_data: &WeatherData,
+    hours: usize,
+) {
+    let now = Local::now();
+    let timezone = config.timezone(location);
+    println!(
+        "{:<9} {:>6} {:>6} {:>10}  {}",
+        fl!("cli-time"),
+        fl!("cli-temp"),
+        fl!("cli-precip"),
+        fl!("cli-wind"),
+        fl!("cli-conditions")
+    );
+    for forecast in weather_data
+        .hourly
+        .iter()
+        .filter(|forecast| forecast.interval.end > now)
+        .take_while(|forecast| forecast.time() < now + Duration::hours(hours as i64))
+    {
+        let conditions = &forecast.conditions;
+        println!(
+            "{:<9} {:>6} {:>6} {:>10}  {}",
+            config.timefmt.format_in(&forecast.time(), timezone).trim(),
+            temperature(config, conditions.air_temperature),
+            forecast
+                .probability_of_precipitation
+                .map(|probability| format!("{:.0}%", probability.0))
+                .unwrap_or_else(|| "-".to_string()),
+            conditions
+                .wind_speed
+                .map(|speed| format!(
+                    "{:.1} {}",
+                    config.speed_units.convert(speed),
+                    config.speed_units.label()
+                ))
+                .unwrap_or_else(|| "-".to_string()),
+            forecast
+                .symbol
+                .map(|symbol| symbol.description())
+                .unwrap_or_default()
+        );
+    }
+}
+
+fn print_daily(config: &WeatherConfig, weather_data: &WeatherData, days: usize) {
+    let now = Local::now();
+    println!(
+        "{:<10} {:>6} {:>6} {:>16}  {}",
+        fl!("cli-day"),
+        fl!("cli-high"),
+        fl!("cli-low"),
+        fl!("cli-precip"),
+        fl!("cli-conditions")
+    );
+    for day in weather_data
+        .daily
+        .iter()
+        .filter(|day| day.interval.end > now)
+        .take(days)
+    {
+        println!(
+            "{:<10} {:>6} {:>6} {:>16}  {}",
+            day.date.format("%a %d"),
+            temperature(config, day.air_temperature_max),
+            temperature(config, day.air_temperature_min),
+            precipitation(config, day),
+            day.symbol
+                .map(|symbol| symbol.description())
+                .unwrap_or_default()
+        );
+    }
+}
+
+/// Precipitation amount of `day` in the configured unit and its probability,
+/// e.g. "1.2–3.4 mm 40%".
+fn precipitation(config: &WeatherConfig, day: &DailyForecast) -> String {
+    let parts: Vec<String> = [
+        config.precipitation_units.format(
+            day.precipitation_amount,
+            day.precipitation_amount_min,
+            day.precipitation_amount_max,
+        ),
+        day.probability_of_precipitation
+            .map(|probability| format!("{:.0}%", probability.0)),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if parts.is_empty() {
+        "-".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
@@ -0,0 +1,186 @@
+use std::str::FromStr;
+
+use chrono::Local;
+use serde::Serialize;
+
+use crate::app::config::{SavedLocation, WeatherConfig};
+use crate::fl;
+use crate::model::weather::WeatherData;
+
+/// Single line output understood by status bars.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum StatusFormat {
+    /// Waybar custom module JSON with `text`, `tooltip` and `class`.
+    #[default]
+    Waybar,
+    /// A single i3bar block object for i3blocks' `format=json` and similar
+    /// wrappers that run the command once per update.
+    ///
+    /// This is not a full i3bar protocol stream, which starts with a
+    /// `{"version":1}` header followed by an endless array of status lines, so
+    /// it cannot be used as i3bar's `status_command` directly.
+    I3blocks,
+    /// Plain text, e.g. for polybar `custom/script` modules.
+    Text,
+}
+
+impl FromStr for StatusFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "waybar" => Ok(Self::Waybar),
+            // Former name, kept so existing i3blocks configs keep working
+            "i3blocks" | "i3bar" => Ok(Self::I3blocks),
+            "text" | "polybar" => Ok(Self::Text),
+            _ => Err(format!(
+                "unknown status format {s:?}, expected waybar, i3blocks or text"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct Waybar {
+    text: String,
+    tooltip: String,
+    class: Vec<&'static str>,
+    alt: String,
+}
+
+#[derive(Debug, Serialize)]
+struct I3blocksBlock {
+    name: &'static str,
+    instance: String,
+    full_text: String,
+    short_text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    urgent: Option<bool>,
+}
+
+/// Current conditions of `location` in the configured units.
+struct Summary {
+    text: String,
+    description: String,
+    tooltip: Vec<String>,
+    class: Vec<&'static str>,
+    alt: String,
+    severe: bool,
+}
+
+impl Summary {
+    fn new(config: &WeatherConfig, location: &SavedLocation, weather_data: &WeatherData) -> Self {
+        let current = weather_data.current_at(Local::now());
+        let symbol = current.and_then(|current| current.symbol);
+        let conditions = current
+            .map(|current| current.conditions.clone())
+            .unwrap_or_default();
+
+        let temperature = conditions
+            .air_temperature
+            .map(|value| format!("{:.0}{}", config.units.convert(value), config.units.label()))
+            .unwrap_or_else(|| "-".to_string());
+
+        let description = symbol
+            .map(|symbol| symbol.description())
+            .unwrap_or_default();
+
+        let mut tooltip = vec![location.name.clone()];
+        if !description.is_empty() {
+            tooltip.push(description.clone());
+        }
+        tooltip.push(format!("{}: {temperature}", fl!("cli-temperature")));
+        if let Some(value) = conditions.apparent_temperature {
+            tooltip.push(format!(
+                "{}: {:.0}{}",
+                fl!("cli-feels-like"),
+                config.units.convert(value),
+                config.units.label()
+            ));
+        }
+        if let Some(value) = conditions.relative_humidity {
+            tooltip.push(format!("{}: {:.0} %", fl!("cli-humidity"), value.0));
+        }
+        if let Some(value) = conditions.wind_speed {
+            tooltip.push(format!(
+                "{}: {:.1} {}",
+                fl!("cli-wind"),
+                config.speed_units.convert(value),
+                config.speed_units.label()
+            ));
+        }
+        tooltip.push(format!(
+            "{}: {}",
+            fl!("cli-updated"),
+            config
+                .timefmt
+                .format_in(&weather_data.updated_at, config.timezone(location))
+                .trim()
+        ));
+
+        let mut class = vec![symbol.map_or("unknown", |symbol| symbol.category())];
+        if let Some(variant) = symbol.and_then(|symbol| symbol.variant()) {
+            class.push(variant.name());
+        }
+
+        Self {
+            text: temperature,
+            description,
+            tooltip,
+            severe: class.contains(&"thunder"),
+            class,
+            alt: symbol.map(|symbol| symbol.code()).unwrap_or_default(),
+        }
+    }
+
+    fn error(err: &str) -> Self {
+        Self {
+            text: "-".to_string(),
+            description: String::new(),
+            tooltip: vec![err.to_string()],
+            class: vec!["error"],
+            alt: String::new(),
+            severe: false,
+        }
+    }
+
+    fn format(self, format: StatusFormat) -> String {
+        let full_text = format!("{} {}", self.description, self.text)
+            .trim()
+            .to_string();
+        match format {
+            StatusFormat::Waybar => serde_json::to_string(&Waybar {
+                text: self.text,
+                tooltip: self.tooltip.join("\n"),
+                class: self.class,
+                alt: self.alt,
+            })
+            .expect("waybar output is valid JSON"),
+            StatusFormat::I3blocks => serde_json::to_string(&I3blocksBlock {
+                name: "forecast",
+                instance: self.alt,
+                full_text,
+                short_text: self.text,
+                urgent: self.severe.then_some(true),
+            })
+            .expect("i3blocks output is valid JSON"),
+            StatusFormat::Text => full_text,
+        }
+    }
+}
+
+/// Print a one line summary of the current conditions.
+///
+/// Errors are printed in the requested format as well, so the bar shows them
+/// instead of an empty module.
+pub fn print_status(
+    config: &WeatherConfig,
+    status: Result<(&SavedLocation, WeatherData), String>,
+    format: StatusFormat,
+) {
+    let summary = match status {
+        Ok((location, weather_data)) => Summary::new(config, location, &weather_data),
+        Err(err) => Summary::error(&err),
+    };
+    println!("{}", summary.format(format));
+}
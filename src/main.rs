@@ -4,9 +4,25 @@ use app::{
 };
 
 mod app;
+mod cli;
 mod model;
 mod views;
 
 fn main() -> cosmic::iced::Result {
+    match cli::Command::parse(std::env::args().skip(1)) {
+        Ok(Some(command)) => {
+            if let Err(err) = command.run() {
+                eprintln!("Error: {err}");
+                std::process::exit(1);
+            }
+            return Ok(());
+        }
+        Ok(None) => {}
+        Err(err) => {
+            eprintln!("Error: {err}\nRun `cosmic-ext-forecast help` for usage.");
+            std::process::exit(2);
+        }
+    }
+
     cosmic::app::run::<App>(settings(), flags())
 }
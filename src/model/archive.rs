@@ -0,0 +1,304 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use cosmic::Application;
+use rusqlite::{params, Connection, OptionalExtension};
+
+use crate::app::App;
+use crate::model::cache::round_coordinates;
+use crate::model::error::ForecastError;
+use crate::model::provider::Provider;
+use crate::model::weather::WeatherData;
+
+/// Retention periods in days selectable in the settings. 0 disables the archive.
+pub const RETENTION_DAYS: &[u32] = &[0, 7, 30, 90, 365];
+
+/// Every forecast fetched, keyed by provider, rounded coordinates and issue time.
+pub struct Archive {
+    connection: Connection,
+}
+
+/// Timestamps are stored as RFC 3339 in UTC, so they sort as text.
+fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+impl Archive {
+    /// `$XDG_DATA_HOME/com.jwestall.Forecast/archive.sqlite3`.
+    ///
+    /// Unlike the cache this is kept in the data directory, as snapshots
+    /// cannot be fetched again.
+    pub fn path() -> Option<PathBuf> {
+        Some(dirs::data_dir()?.join(App::APP_ID).join("archive.sqlite3"))
+    }
+
+    pub fn open() -> Result<Self, ForecastError> {
+        let Some(path) = Self::path() else {
+            return Err(ForecastError::Io(
+                "Could not find the data directory.".to_string(),
+            ));
+        };
+        std::fs::create_dir_all(path.parent().expect("archive is in a directory"))?;
+
+        Self::with_connection(Connection::open(path)?)
+    }
+
+    fn with_connection(connection: Connection) -> Result<Self, ForecastError> {
+        connection.execute_batch(
+            "CREATE TABLE IF NOT EXISTS snapshots (
+                provider TEXT NOT NULL,
+                latitude REAL NOT NULL,
+                longitude REAL NOT NULL,
+                issued_at TEXT NOT NULL,
+                fetched_at TEXT NOT NULL,
+                weather_data TEXT NOT NULL,
+                PRIMARY KEY (provider, latitude, longitude, issued_at)
+            );
+            CREATE INDEX IF NOT EXISTS snapshots_fetched_at ON snapshots (fetched_at);",
+        )?;
+        Ok(Self { connection })
+    }
+
+    /// Store a fetched forecast. Forecasts already archived with the same
+    /// issue time are kept as they are.
+    pub fn insert(
+        &self,
+        provider: Provider,
+        coords: (f64, f64),
+        weather_data: &WeatherData,
+        fetched_at: DateTime<Utc>,
+    ) -> Result<(), ForecastError> {
+        let (latitude, longitude) = round_coordinates(coords);
+        self.connection.execute(
+            "INSERT OR IGNORE INTO snapshots VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                provider.id(),
+                latitude,
+                longitude,
+                timestamp(weather_data.updated_at.to_utc()),
+                timestamp(fetched_at),
+                serde_json::to_string(weather_data).expect("forecast is valid JSON"),
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Remove forecasts fetched more than `days` days before `now`.
+    pub fn prune(&self, days: u32, now: DateTime<Utc>) -> Result<usize, ForecastError> {
+        let cutoff = now - chrono::Duration::days(days.into());
+        Ok(self.connection.execute(
+            "DELETE FROM snapshots WHERE fetched_at < ?1",
+            params![timestamp(cutoff)],
+        )?)
+    }
+
+    /// Issue times of the archived forecasts for `coords`, oldest first.
+    pub fn issue_times(
+        &self,
+        provider: Provider,
+        coords: (f64, f64),
+    ) -> Result<Vec<DateTime<Utc>>, ForecastError> {
+        let (latitude, longitude) = round_coordinates(coords);
+        let mut statement = self.connection.prepare(
+            "SELECT issued_at FROM snapshots
+             WHERE provider = ?1 AND latitude = ?2 AND longitude = ?3
+             ORDER BY issued_at",
+        )?;
+        let issue_times = statement
+            .query_map(params![provider.id(), latitude, longitude], |row| {
+                row.get::<_, String>(0)
+            })?
+            .filter_map(|issued_at| {
+                DateTime::parse_from_rfc3339(&issued_at.ok()?)
+                    .ok()
+                    .map(|issued_at| issued_at.to_utc())
+            })
+            .collect();
+        Ok(issue_times)
+    }
+
+    /// The newest forecast for `coords` issued at or before `time`.
+    pub fn forecast_at(
+        &self,
+        provider: Provider,
+        coords: (f64, f64),
+        time: DateTime<Utc>,
+    ) -> Result<Option<WeatherData>, ForecastError> {
+        let (latitude, longitude) = round_coordinates(coords);
+        let weather_data: Option<String> = self
+            .connection
+            .query_row(
+                "SELECT weather_data FROM snapshots
+                 WHERE provider = ?1 AND latitude = ?2 AND longitude = ?3 AND issued_at <= ?4
+                 ORDER BY issued_at DESC LIMIT 1",
+                params![provider.id(), latitude, longitude, timestamp(time)],
+                |row| row.get(0),
+            )
+            .optional()?;
+
+        weather_data
+            .map(|weather_data| {
+                serde_json::from_str(&weather_data).map_err(|error| ForecastError::Decode {
+                    error: error.to_string(),
+                    payload: String::new(),
+                })
+            })
+            .transpose()
+    }
+}
+
+/// Run `f` on the archive.
+///
+/// SQLite blocks, so this runs on the blocking thread pool.
+async fn with_archive<T: Send + 'static>(
+    f: impl FnOnce(&Archive) -> Result<T, ForecastError> + Send + 'static,
+) -> Result<T, ForecastError> {
+    tokio::task::spawn_blocking(move || f(&Archive::open()?))
+        .await
+        .unwrap_or_else(|err| Err(ForecastError::Io(err.to_string())))
+}
+
+/// Archive a fetched forecast and drop the ones older than `retention_days`.
+///
+/// With a retention of 0 nothing is archived and the archive is emptied.
+pub async fn record(
+    provider: Provider,
+    coords: (f64, f64),
+    weather_data: WeatherData,
+    retention_days: u32,
+) -> Result<(), ForecastError> {
+    if retention_days == 0 {
+        return prune(retention_days).await;
+    }
+
+    with_archive(move |archive| {
+        let now = Utc::now();
+        archive.insert(provider, coords, &weather_data, now)?;
+        archive.prune(retention_days, now)?;
+        Ok(())
+    })
+    .await
+}
+
+/// Drop the forecasts older than `retention_days`, all of them for 0.
+///
+/// Run at startup and whenever the retention changes, so a shorter retention
+/// applies without waiting for the next fetch.
+pub async fn prune(retention_days: u32) -> Result<(), ForecastError> {
+    // Do not create an archive just to empty it
+    if !Archive::path().is_some_and(|path| path.exists()) {
+        return Ok(());
+    }
+
+    with_archive(move |archive| {
+        let removed = archive.prune(retention_days, Utc::now())?;
+        if removed > 0 {
+            log::info!("removed {removed} archived forecasts");
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// Issue times of the archived forecasts for `coords`, oldest first.
+pub async fn issue_times(
+    provider: Provider,
+    coords: (f64, f64),
+) -> Result<Vec<DateTime<Utc>>, ForecastError> {
+    with_archive(move |archive| archive.issue_times(provider, coords)).await
+}
+
+/// The newest forecast for `coords` issued at or before `time`.
+pub async fn forecast_at(
+    provider: Provider,
+    coords: (f64, f64),
+    time: DateTime<Utc>,
+) -> Result<Option<WeatherData>, ForecastError> {
+    with_archive(move |archive| archive.forecast_at(provider, coords, time)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, Local, TimeZone};
+
+    use super::*;
+
+    const BERLIN: (f64, f64) = (52.52, 13.405);
+
+    fn archive() -> Archive {
+        Archive::with_connection(Connection::open_in_memory().unwrap()).unwrap()
+    }
+
+    fn at(hours: i64) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0).unwrap() + Duration::hours(hours)
+    }
+
+    fn weather_data(updated_at: DateTime<Utc>) -> WeatherData {
+        WeatherData {
+            updated_at: updated_at.with_timezone(&Local),
+            coordinates: BERLIN,
+            current: None,
+            hourly: Vec::new(),
+            daily: Vec::new(),
+        }
+    }
+
+    /// Archive forecasts issued and fetched at the given hours.
+    fn archive_at(hours: &[i64]) -> Archive {
+        let archive = archive();
+        for hour in hours {
+            archive
+                .insert(Provider::MetNo, BERLIN, &weather_data(at(*hour)), at(*hour))
+                .unwrap();
+        }
+        archive
+    }
+
+    #[test]
+    fn issue_times() {
+        let archive = archive_at(&[6, 0, 3]);
+        assert_eq!(
+            archive.issue_times(Provider::MetNo, BERLIN).unwrap(),
+            [at(0), at(3), at(6)]
+        );
+        assert!(archive
+            .issue_times(Provider::OpenMeteo, BERLIN)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn forecast_in_effect() {
+        let archive = archive_at(&[0, 3, 6]);
+        let forecast_at = |hours| {
+            archive
+                .forecast_at(Provider::MetNo, BERLIN, at(hours))
+                .unwrap()
+                .map(|weather_data| weather_data.updated_at.to_utc())
+        };
+        assert_eq!(forecast_at(-1), None);
+        assert_eq!(forecast_at(0), Some(at(0)));
+        assert_eq!(forecast_at(5), Some(at(3)));
+        assert_eq!(forecast_at(24), Some(at(6)));
+    }
+
+    #[test]
+    fn prune_by_retention() {
+        let archive = archive_at(&[-72, -24, -1]);
+        assert_eq!(archive.prune(2, at(0)).unwrap(), 1);
+        assert_eq!(
+            archive.issue_times(Provider::MetNo, BERLIN).unwrap(),
+            [at(-24), at(-1)]
+        );
+    }
+
+    #[test]
+    fn prune_everything_without_retention() {
+        let archive = archive_at(&[-72, -24, -1]);
+        assert_eq!(archive.prune(0, at(0)).unwrap(), 3);
+        assert!(archive
+            .issue_times(Provider::MetNo, BERLIN)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use cosmic::Application;
+
+use crate::app::App;
+use crate::model::error::ForecastError;
+use crate::model::provider::Provider;
+use crate::model::weather::WeatherData;
+
+/// Largest total size of the cached forecasts. The least recently written
+/// ones are removed first.
+const MAX_CACHE_BYTES: u64 = 16 * 1024 * 1024;
+/// Cached forecasts not written for this long are removed.
+const MAX_AGE: Duration = Duration::from_secs(14 * 24 * 60 * 60);
+
+/// `$XDG_CACHE_HOME/com.jwestall.Forecast/forecasts`.
+pub fn cache_dir() -> Option<PathBuf> {
+    Some(dirs::cache_dir()?.join(App::APP_ID).join("forecasts"))
+}
+
+/// Round coordinates to three decimals, about 100 m, which is finer than the
+/// grid of any provider, so forecasts of nearby locations are shared.
+pub fn round_coordinates((latitude, longitude): (f64, f64)) -> (f64, f64) {
+    let round = |value: f64| (value * 1000.0).round() / 1000.0;
+    (round(latitude), round(longitude))
+}
+
+/// File of the forecast for `coords` by `provider`.
+fn path(provider: Provider, coords: (f64, f64)) -> Option<PathBuf> {
+    let (latitude, longitude) = round_coordinates(coords);
+    Some(cache_dir()?.join(format!(
+        "{}_{:.3}_{:.3}.json",
+        provider.id(),
+        latitude,
+        longitude
+    )))
+}
+
+/// Read the cached forecast, `None` if there is none or it cannot be decoded.
+pub async fn load(provider: Provider, coords: (f64, f64)) -> Option<WeatherData> {
+    let path = path(provider, coords)?;
+    let contents = match tokio::fs::read(&path).await {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(err) => {
+            log::warn!("failed to read {}: {}", path.display(), err);
+            return None;
+        }
+    };
+    serde_json::from_slice(&contents)
+        .map_err(|err| log::warn!("discarding cached forecast {}: {}", path.display(), err))
+        .ok()
+}
+
+/// Write the forecast to the cache and evict old entries.
+///
+/// The file is written next to its destination and renamed, so readers never
+/// see a partially written forecast.
+pub async fn store(
+    provider: Provider,
+    coords: (f64, f64),
+    weather_data: WeatherData,
+) -> Result<(), ForecastError> {
+    let Some(path) = path(provider, coords) else {
+        return Err(ForecastError::Io(
+            "Could not find the cache directory.".to_string(),
+        ));
+    };
+    let directory = path.parent().expect("cache files are in a directory");
+    tokio::fs::create_dir_all(directory).await?;
+
+    let contents = serde_json::to_vec(&weather_data).expect("forecast is valid JSON");
+    let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+    tokio::fs::write(&temporary, contents).await?;
+    if let Err(err) = tokio::fs::rename(&temporary, &path).await {
+        let _ = tokio::fs::remove_file(&temporary).await;
+        return Err(err.into());
+    }
+
+    evict(directory, &path, MAX_AGE, MAX_CACHE_BYTES).await?;
+    Ok(())
+}
+
+/// Remove cached forecasts older than `max_age`, then the oldest ones until
+/// the cache fits into `max_bytes`. `keep` is never removed.
+///
+/// Only `.json` files are considered, so temporary files another instance is
+/// still writing are left alone.
+async fn evict(
+    directory: &Path,
+    keep: &Path,
+    max_age: Duration,
+    max_bytes: u64,
+) -> Result<(), ForecastError> {
+    let now = SystemTime::now();
+    let mut entries = Vec::new();
+    let mut read_dir = tokio::fs::read_dir(directory).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        let path = entry.path();
+        if path.extension().and_then(|extension| extension.to_str()) != Some("json") {
+            continue;
+        }
+        let metadata = match entry.metadata().await {
+            Ok(metadata) => metadata,
+            // Another instance evicted it first
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err.into()),
+        };
+        if metadata.is_file() {
+            let modified = metadata.modified().unwrap_or(now);
+            entries.push((modified, metadata.len(), path));
+        }
+    }
+
+    // Newest first, so everything after the size limit is removed
+    entries.sort_by(|a, b| b.0.cmp(&a.0));
+    let mut size = 0;
+    for (modified, len, path) in entries {
+        if path == keep {
+            size += len;
+            continue;
+        }
+        let expired = now.duration_since(modified).is_ok_and(|age| age > max_age);
+        if expired || size + len > max_bytes {
+            log::info!("evicting cached forecast {}", path.display());
+            match tokio::fs::remove_file(&path).await {
+                Ok(()) => {}
+                // Another instance evicted it first
+                Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+                Err(err) => return Err(err.into()),
+            }
+        } else {
+            size += len;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(60 * 60);
+
+    /// Empty directory for the test `name`, removed again when dropped.
+    struct TestDir(PathBuf);
+
+    impl TestDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "forecast-cache-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = std::fs::remove_dir_all(&path);
+            std::fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+
+        /// Write a file of `len` bytes last modified `age` ago.
+        fn file(&self, name: &str, len: usize, age: Duration) -> PathBuf {
+            let path = self.0.join(name);
+            let file = std::fs::File::create(&path).unwrap();
+            file.set_len(len as u64).unwrap();
+            file.set_modified(SystemTime::now() - age).unwrap();
+            path
+        }
+
+        fn files(&self) -> Vec<String> {
+            let mut files: Vec<String> = std::fs::read_dir(&self.0)
+                .unwrap()
+                .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+                .collect();
+            files.sort();
+            files
+        }
+    }
+
+    impl Drop for TestDir {
+        fn drop(&mut self) {
+            let _ = std::fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[tokio::test]
+    async fn evict_by_age() {
+        let dir = TestDir::new("age");
+        let keep = dir.file("keep.json", 10, 30 * 24 * HOUR);
+        dir.file("old.json", 10, 3 * HOUR);
+        dir.file("new.json", 10, HOUR);
+
+        evict(&dir.0, &keep, 2 * HOUR, 1000).await.unwrap();
+
+        assert_eq!(dir.files(), ["keep.json", "new.json"]);
+    }
+
+    #[tokio::test]
+    async fn evict_oldest_by_size() {
+        let dir = TestDir::new("size");
+        let keep = dir.file("keep.json", 40, HOUR);
+        dir.file("a.json", 40, 4 * HOUR);
+        dir.file("b.json", 40, 3 * HOUR);
+        dir.file("c.json", 40, 2 * HOUR);
+
+        evict(&dir.0, &keep, MAX_AGE, 100).await.unwrap();
+
+        assert_eq!(dir.files(), ["c.json", "keep.json"]);
+    }
+
+    #[tokio::test]
+    async fn keep_temporary_files() {
+        let dir = TestDir::new("temporary");
+        let keep = dir.file("keep.json", 10, HOUR);
+        dir.file("other.json.1234.tmp", 1000, 30 * 24 * HOUR);
+
+        evict(&dir.0, &keep, HOUR / 2, 10).await.unwrap();
+
+        assert_eq!(dir.files(), ["keep.json", "other.json.1234.tmp"]);
+    }
+}
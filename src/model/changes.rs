@@ -0,0 +1,425 @@
+use chrono::{DateTime, Datelike, Duration, Local, NaiveDate, Weekday};
+use chrono_tz::Tz;
+
+use crate::app::config::WeatherConfig;
+use crate::fl;
+use crate::model::quantity::Temperature;
+use crate::model::timezone;
+use crate::model::weather::WeatherData;
+
+/// Thresholds selectable in the settings, 0 ignores the variable.
+pub const TEMPERATURE_THRESHOLDS: &[u32] = &[0, 1, 2, 3, 5, 10];
+pub const PRECIPITATION_THRESHOLDS: &[u32] = &[0, 1, 2, 3, 6];
+pub const WIND_SPEED_THRESHOLDS: &[u32] = &[0, 2, 5, 10, 20];
+
+/// Hours ahead compared for precipitation and wind.
+const LOOKAHEAD_HOURS: i64 = 24;
+/// Days ahead compared for highs and lows, including today.
+const LOOKAHEAD_DAYS: usize = 3;
+
+/// Symbol categories with precipitation.
+const PRECIPITATION: &[&str] = &["rain", "sleet", "snow", "thunder"];
+
+/// A change between two forecasts for the same location worth reporting.
+///
+/// Values are in the configured units.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ForecastChange {
+    PrecipitationMoved {
+        category: &'static str,
+        time: DateTime<Local>,
+        previous: DateTime<Local>,
+    },
+    PrecipitationStarted {
+        category: &'static str,
+        time: DateTime<Local>,
+    },
+    PrecipitationStopped {
+        category: &'static str,
+    },
+    High {
+        date: NaiveDate,
+        difference: f64,
+    },
+    Low {
+        date: NaiveDate,
+        difference: f64,
+    },
+    WindSpeed {
+        previous: f64,
+        current: f64,
+    },
+}
+
+impl ForecastChange {
+    /// Localized description, e.g. "Tomorrow's high down 4°F", with times
+    /// in `timezone`.
+    pub fn message(&self, config: &WeatherConfig, timezone: Option<Tz>) -> String {
+        let time =
+            |time: &DateTime<Local>| config.timefmt.format_in(time, timezone).trim().to_string();
+        let day = |date: &NaiveDate| {
+            let today = timezone::date(Local::now(), timezone);
+            if *date == today {
+                "today"
+            } else if today.succ_opt() == Some(*date) {
+                "tomorrow"
+            } else {
+                "other"
+            }
+        };
+        let direction = |difference: f64| if difference > 0.0 { "up" } else { "down" };
+        let temperature =
+            |difference: f64| format!("{:.0}{}", difference.abs(), config.units.label());
+
+        match self {
+            Self::PrecipitationMoved {
+                category,
+                time: start,
+                previous,
+            } => fl!(
+                "change-precipitation-moved",
+                category = *category,
+                time = time(start),
+                previous = time(previous)
+            ),
+            Self::PrecipitationStarted {
+                category,
+                time: start,
+            } => fl!(
+                "change-precipitation-started",
+                category = *category,
+                time = time(start)
+            ),
+            Self::PrecipitationStopped { category } => {
+                fl!("change-precipitation-stopped", category = *category)
+            }
+            Self::High { date, difference } => fl!(
+                "change-high",
+                day = day(date),
+                weekday = fl!("weekday", weekday = weekday(date.weekday())),
+                direction = direction(*difference),
+                difference = temperature(*difference)
+            ),
+            Self::Low { date, difference } => fl!(
+                "change-low",
+                day = day(date),
+                weekday = fl!("weekday", weekday = weekday(date.weekday())),
+                direction = direction(*difference),
+                difference = temperature(*difference)
+            ),
+            Self::WindSpeed { previous, current } => fl!(
+                "change-wind-speed",
+                direction = direction(current - previous),
+                speed = format!("{:.0} {}", current, config.speed_units.label())
+            ),
+        }
+    }
+}
+
+/// Selector of the localized name of `weekday`.
+fn weekday(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "monday",
+        Weekday::Tue => "tuesday",
+        Weekday::Wed => "wednesday",
+        Weekday::Thu => "thursday",
+        Weekday::Fri => "friday",
+        Weekday::Sat => "saturday",
+        Weekday::Sun => "sunday",
+    }
+}
+
+/// Start and category of the first precipitation in the next [`LOOKAHEAD_HOURS`].
+fn precipitation_start(
+    weather_data: &WeatherData,
+    now: DateTime<Local>,
+) -> Option<(&'static str, DateTime<Local>)> {
+    weather_data
+        .hourly
+        .iter()
+        .filter(|forecast| forecast.interval.end > now)
+        .take_while(|forecast| forecast.time() < now + Duration::hours(LOOKAHEAD_HOURS))
+        .find_map(|forecast| {
+            let category = forecast.symbol?.category();
+            PRECIPITATION
+                .contains(&category)
+                .then(|| (category, forecast.time()))
+        })
+}
+
+/// Strongest wind in the next [`LOOKAHEAD_HOURS`] in the configured units.
+fn max_wind_speed(
+    config: &WeatherConfig,
+    weather_data: &WeatherData,
+    now: DateTime<Local>,
+) -> Option<f64> {
+    weather_data
+        .hourly
+        .iter()
+        .filter(|forecast| forecast.interval.end > now)
+        .take_while(|forecast| forecast.time() < now + Duration::hours(LOOKAHEAD_HOURS))
+        .filter_map(|forecast| forecast.conditions.wind_speed)
+        .map(|speed| config.speed_units.convert(speed))
+        .reduce(f64::max)
+}
+
+/// Changes of `current` against the `previous` forecast exceeding the
+/// configured thresholds.
+pub fn compare(
+    config: &WeatherConfig,
+    previous: &WeatherData,
+    current: &WeatherData,
+    now: DateTime<Local>,
+) -> Vec<ForecastChange> {
+    let thresholds = config.change_thresholds;
+    let mut changes = Vec::new();
+
+    if thresholds.precipitation_hours > 0 {
+        let threshold = Duration::hours(thresholds.precipitation_hours.into());
+        match (
+            precipitation_start(previous, now),
+            precipitation_start(current, now),
+        ) {
+            (Some((_, previous)), Some((category, time)))
+                if (time - previous).abs() >= threshold =>
+            {
+                changes.push(ForecastChange::PrecipitationMoved {
+                    category,
+                    time,
+                    previous,
+                });
+            }
+            (None, Some((category, time))) => {
+                changes.push(ForecastChange::PrecipitationStarted { category, time });
+            }
+            (Some((category, _)), None) => {
+                changes.push(ForecastChange::PrecipitationStopped { category });
+            }
+            _ => {}
+        }
+    }
+
+    if thresholds.temperature > 0 {
+        let threshold = f64::from(thresholds.temperature);
+        let difference = |previous: Option<Temperature>, current: Option<Temperature>| {
+            let difference = config.units.convert(current?) - config.units.convert(previous?);
+            (difference.abs() >= threshold).then_some(difference)
+        };
+        for day in current
+            .daily
+            .iter()
+            .filter(|day| day.interval.end > now)
+            .take(LOOKAHEAD_DAYS)
+        {
+            let Some(previous_day) = previous
+                .daily
+                .iter()
+                .find(|previous_day| previous_day.date == day.date)
+            else {
+                continue;
+            };
+            let date = day.date;
+            if let Some(difference) =
+                difference(previous_day.air_temperature_max, day.air_temperature_max)
+            {
+                changes.push(ForecastChange::High { date, difference });
+            }
+            if let Some(difference) =
+                difference(previous_day.air_temperature_min, day.air_temperature_min)
+            {
+                changes.push(ForecastChange::Low { date, difference });
+            }
+        }
+    }
+
+    if thresholds.wind_speed > 0 {
+        if let (Some(previous), Some(current)) = (
+            max_wind_speed(config, previous, now),
+            max_wind_speed(config, current, now),
+        ) {
+            if (current - previous).abs() >= f64::from(thresholds.wind_speed) {
+                changes.push(ForecastChange::WindSpeed { previous, current });
+            }
+        }
+    }
+
+    changes
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{TimeZone, Utc};
+
+    use super::*;
+    use crate::app::config::{ChangeThresholds, SpeedUnits, Units};
+    use crate::model::quantity::Speed;
+    use crate::model::symbol::WeatherSymbol;
+    use crate::model::weather::{Conditions, DailyForecast, HourlyForecast, Interval};
+
+    fn now() -> DateTime<Local> {
+        Utc.with_ymd_and_hms(2024, 6, 1, 12, 0, 0)
+            .unwrap()
+            .with_timezone(&Local)
+    }
+
+    /// Celsius and metres per second, reporting 3° highs and lows, 2 hour
+    /// shifts of precipitation and 5 m/s of wind.
+    fn config() -> WeatherConfig {
+        WeatherConfig {
+            units: Units::Celsius,
+            speed_units: SpeedUnits::MetersPerSecond,
+            change_thresholds: ChangeThresholds {
+                temperature: 3,
+                precipitation_hours: 2,
+                wind_speed: 5,
+            },
+            ..Default::default()
+        }
+    }
+
+    /// Hourly forecast for the next day with rain from hour `rain_from` and
+    /// wind of `wind_speed` m/s.
+    fn hourly(rain_from: Option<i64>, wind_speed: f64) -> WeatherData {
+        let hourly = (0..LOOKAHEAD_HOURS)
+            .map(|hour| {
+                let start = now() + Duration::hours(hour);
+                let rain = rain_from.is_some_and(|rain_from| hour >= rain_from);
+                HourlyForecast {
+                    interval: Interval {
+                        start,
+                        end: start + Duration::hours(1),
+                    },
+                    conditions: Conditions {
+                        wind_speed: Some(Speed(wind_speed)),
+                        ..Default::default()
+                    },
+                    symbol: Some(if rain {
+                        WeatherSymbol::Rain
+                    } else {
+                        WeatherSymbol::Cloudy
+                    }),
+                    probability_of_precipitation: None,
+                    precipitation_amount: None,
+                    air_temperature_max: None,
+                    air_temperature_min: None,
+                    precipitation_windows: Vec::new(),
+                }
+            })
+            .collect();
+        weather_data(hourly, Vec::new())
+    }
+
+    /// Daily forecast of today with the given high and low in °C.
+    fn today(high: f64, low: f64) -> WeatherData {
+        let day = DailyForecast {
+            date: now().date_naive(),
+            interval: Interval {
+                start: now() - Duration::hours(12),
+                end: now() + Duration::hours(12),
+            },
+            symbol: None,
+            air_temperature_max: Some(Temperature(high)),
+            air_temperature_min: Some(Temperature(low)),
+            probability_of_precipitation: None,
+            precipitation_amount: None,
+            precipitation_amount_min: None,
+            precipitation_amount_max: None,
+            wind_speed_max: None,
+            wind_speed_of_gust_max: None,
+        };
+        weather_data(Vec::new(), vec![day])
+    }
+
+    fn weather_data(hourly: Vec<HourlyForecast>, daily: Vec<DailyForecast>) -> WeatherData {
+        WeatherData {
+            updated_at: now(),
+            coordinates: (52.52, 13.405),
+            current: None,
+            hourly,
+            daily,
+        }
+    }
+
+    fn changes(previous: &WeatherData, current: &WeatherData) -> Vec<ForecastChange> {
+        compare(&config(), previous, current, now())
+    }
+
+    #[test]
+    fn precipitation_moved() {
+        let previous = hourly(Some(6), 0.0);
+        assert!(changes(&previous, &hourly(Some(7), 0.0)).is_empty());
+        assert_eq!(
+            changes(&previous, &hourly(Some(8), 0.0)),
+            [ForecastChange::PrecipitationMoved {
+                category: "rain",
+                time: now() + Duration::hours(8),
+                previous: now() + Duration::hours(6),
+            }]
+        );
+        assert_eq!(changes(&previous, &hourly(Some(3), 0.0)).len(), 1);
+    }
+
+    #[test]
+    fn precipitation_started_and_stopped() {
+        assert_eq!(
+            changes(&hourly(None, 0.0), &hourly(Some(6), 0.0)),
+            [ForecastChange::PrecipitationStarted {
+                category: "rain",
+                time: now() + Duration::hours(6),
+            }]
+        );
+        assert_eq!(
+            changes(&hourly(Some(6), 0.0), &hourly(None, 0.0)),
+            [ForecastChange::PrecipitationStopped { category: "rain" }]
+        );
+    }
+
+    #[test]
+    fn high_and_low() {
+        let date = now().date_naive();
+        let previous = today(20.0, 10.0);
+        assert!(changes(&previous, &today(22.9, 7.1)).is_empty());
+
+        let [ForecastChange::High {
+            date: high_date,
+            difference: high,
+        }, ForecastChange::Low {
+            date: low_date,
+            difference: low,
+        }] = changes(&previous, &today(23.1, 6.9))[..]
+        else {
+            panic!("expected a high and a low");
+        };
+        assert_eq!((high_date, low_date), (date, date));
+        assert!((high - 3.1).abs() < 1e-9);
+        assert!((low + 3.1).abs() < 1e-9);
+    }
+
+    #[test]
+    fn wind_speed() {
+        let previous = hourly(None, 10.0);
+        assert!(changes(&previous, &hourly(None, 14.9)).is_empty());
+        assert_eq!(
+            changes(&previous, &hourly(None, 15.1)),
+            [ForecastChange::WindSpeed {
+                previous: 10.0,
+                current: 15.1,
+            }]
+        );
+        assert_eq!(changes(&previous, &hourly(None, 4.9)).len(), 1);
+    }
+
+    #[test]
+    fn disabled_thresholds() {
+        let mut config = config();
+        config.change_thresholds = ChangeThresholds {
+            temperature: 0,
+            precipitation_hours: 0,
+            wind_speed: 0,
+        };
+        let previous = hourly(None, 0.0);
+        let current = hourly(Some(1), 20.0);
+        assert!(compare(&config, &previous, &current, now()).is_empty());
+        assert!(compare(&config, &today(0.0, 0.0), &today(10.0, 10.0), now()).is_empty());
+    }
+}
@@ -0,0 +1,288 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+use crate::fl;
+
+/// Number of characters of a response body kept around a decode error.
+const PAYLOAD_CONTEXT: usize = 80;
+
+/// Errors while fetching forecasts, looking up locations or storing config.
+#[derive(Clone, Debug)]
+pub enum ForecastError {
+    /// The request could not be sent or the response not received.
+    Network(String),
+    /// The server answered with an error status.
+    Status { status: u16, url: String },
+    /// The response body is not in the documented format.
+    Decode { error: String, payload: String },
+    /// Stored coordinates of a saved location cannot be parsed.
+    InvalidCoordinates { latitude: String, longitude: String },
+    /// The geocoder found nothing for the query.
+    LocationNotFound(String),
+    /// A request succeeded but returned no forecast.
+    NoWeatherData,
+    /// Config, state, cache, archive or exported files could not be read or written.
+    Io(String),
+    /// Proxy or certificate settings cannot be applied.
+    NetworkSettings(String),
+    /// Network access is disabled in the settings.
+    Offline,
+}
+
+impl ForecastError {
+    /// Localized message for the user.
+    pub fn message(&self) -> String {
+        match self {
+            Self::Network(error) => fl!("error-network", error = error.as_str()),
+            Self::Status { status: 403, .. } => fl!("error-status-forbidden"),
+            Self::Status { status: 429, .. } => fl!("error-status-too-many-requests"),
+            Self::Status { status, .. } if *status >= 500 => {
+                fl!("error-status-server", status = status.to_string())
+            }
+            Self::Status { status, .. } => fl!("error-status", status = status.to_string()),
+            Self::Decode { error, .. } => fl!("error-decode", error = error.as_str()),
+            Self::InvalidCoordinates {
+                latitude,
+                longitude,
+            } => fl!(
+                "error-invalid-coordinates",
+                latitude = latitude.as_str(),
+                longitude = longitude.as_str()
+            ),
+            Self::LocationNotFound(query) => {
+                fl!("error-location-not-found", query = query.as_str())
+            }
+            Self::NoWeatherData => fl!("error-no-weather-data"),
+            Self::Io(error) => fl!("error-io", error = error.as_str()),
+            Self::NetworkSettings(error) => {
+                fl!("error-network-settings", error = error.as_str())
+            }
+            Self::Offline => fl!("error-offline"),
+        }
+    }
+
+    /// Whether sending the same request again may succeed.
+    ///
+    /// met.no answers `403 Forbidden` to clients violating its terms of
+    /// service, retrying those only makes it worse.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Self::Network(_) | Self::NoWeatherData => true,
+            Self::Status { status, .. } => *status == 429 || *status >= 500,
+            Self::Decode { .. }
+            | Self::InvalidCoordinates { .. }
+            | Self::LocationNotFound(_)
+            | Self::Io(_)
+            | Self::NetworkSettings(_)
+            | Self::Offline => false,
+        }
+    }
+
+    /// Check the status of `response` and decode its JSON body.
+    ///
+    /// Decode errors keep the part of the body around the error position.
+    pub async fn json<T: DeserializeOwned>(response: reqwest::Response) -> Result<T, Self> {
+        let body = Self::check_status(response)?.text().await?;
+        serde_json::from_str(&body).map_err(|error| Self::Decode {
+            payload: payload_context(&body, &error),
+            error: error.to_string(),
+        })
+    }
+
+    /// Turn error statuses into [`ForecastError::Status`].
+    pub fn check_status(response: reqwest::Response) -> Result<reqwest::Response, Self> {
+        let status = response.status();
+        if status.is_client_error() || status.is_server_error() {
+            return Err(Self::Status {
+                status: status.as_u16(),
+                url: response.url().to_string(),
+            });
+        }
+        Ok(response)
+    }
+}
+
+impl fmt::Display for ForecastError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message())
+    }
+}
+
+impl std::error::Error for ForecastError {}
+
+impl From<reqwest::Error> for ForecastError {
+    fn from(error: reqwest::Error) -> Self {
+        match (error.status(), error.url()) {
+            (Some(status), Some(url)) => Self::Status {
+                status: status.as_u16(),
+                url: url.to_string(),
+            },
+            _ if error.is_decode() => Self::Decode {
+                error: error.to_string(),
+                payload: String::new(),
+            },
+            _ => Self::Network(error.to_string()),
+        }
+    }
+}
+
+impl From<std::io::Error> for ForecastError {
+    fn from(error: std::io::Error) -> Self {
+        Self::Io(error.to_string())
+    }
+}
+
+impl From<rusqlite::Error> for ForecastError {
+    fn from(error: rusqlite::Error) -> Self {
+        Self::Io(error.to_string())
+    }
+}
+
+/// The line of `body` the decode error points at, shortened to [`PAYLOAD_CONTEXT`].
+fn payload_context(body: &str, error: &serde_json::Error) -> String {
+    let line = body
+        .lines()
+        .nth(error.line().saturating_sub(1))
+        .unwrap_or(body);
+    let column = error.column().min(line.chars().count());
+    line.chars()
+        .skip(column.saturating_sub(PAYLOAD_CONTEXT / 2))
+        .take(PAYLOAD_CONTEXT)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde::Deserialize;
+
+    use super::*;
+    use crate::model::mock_server::{MockResponse, MockServer};
+
+    fn status(status: u16) -> ForecastError {
+        ForecastError::Status {
+            status,
+            url: "https://api.met.no/weatherapi/locationforecast/2.0/complete".to_string(),
+        }
+    }
+
+    #[test]
+    fn messages_include_details() {
+        let network = ForecastError::Network("connection refused".to_string()).message();
+        assert!(network.contains("connection refused"), "{network}");
+        let decode = ForecastError::Decode {
+            error: "missing field `properties`".to_string(),
+            payload: String::new(),
+        }
+        .message();
+        assert!(decode.contains("missing field `properties`"), "{decode}");
+        let coordinates = ForecastError::InvalidCoordinates {
+            latitude: "north".to_string(),
+            longitude: "13.4".to_string(),
+        }
+        .message();
+        assert!(
+            coordinates.contains("north") && coordinates.contains("13.4"),
+            "{coordinates}"
+        );
+        let query = ForecastError::LocationNotFound("Atlantis".to_string()).message();
+        assert!(query.contains("Atlantis"), "{query}");
+        let io = ForecastError::Io("permission denied".to_string()).message();
+        assert!(io.contains("permission denied"), "{io}");
+        let settings = ForecastError::NetworkSettings("invalid proxy".to_string()).message();
+        assert!(settings.contains("invalid proxy"), "{settings}");
+        for error in [ForecastError::NoWeatherData, ForecastError::Offline] {
+            assert!(!error.message().is_empty(), "{error:?}");
+        }
+    }
+
+    #[test]
+    fn status_messages() {
+        assert!(status(503).message().contains("503"));
+        assert!(status(404).message().contains("404"));
+        // Forbidden and rate limited requests are explained instead of numbered
+        assert!(!status(403).message().contains("403"));
+        assert!(!status(429).message().contains("429"));
+
+        let messages = [403, 404, 429, 500].map(|code| status(code).message());
+        for (index, message) in messages.iter().enumerate() {
+            assert!(!messages[..index].contains(message), "{messages:?}");
+        }
+    }
+
+    #[test]
+    fn retryable() {
+        for error in [
+            ForecastError::Network("timed out".to_string()),
+            ForecastError::NoWeatherData,
+            status(429),
+            status(500),
+            status(503),
+        ] {
+            assert!(error.is_retryable(), "{error:?}");
+        }
+    }
+
+    #[test]
+    fn not_retryable() {
+        for error in [
+            status(400),
+            status(403),
+            status(404),
+            ForecastError::Decode {
+                error: "expected value".to_string(),
+                payload: String::new(),
+            },
+            ForecastError::InvalidCoordinates {
+                latitude: "north".to_string(),
+                longitude: "13.4".to_string(),
+            },
+            ForecastError::LocationNotFound("Atlantis".to_string()),
+            ForecastError::Io("permission denied".to_string()),
+            ForecastError::NetworkSettings("invalid proxy".to_string()),
+            ForecastError::Offline,
+        ] {
+            assert!(!error.is_retryable(), "{error:?}");
+        }
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Body {
+        value: u32,
+    }
+
+    async fn json(response: MockResponse) -> Result<Body, ForecastError> {
+        let server = MockServer::start(move |_| response.clone());
+        let response = reqwest::get(server.url("/forecast")).await.unwrap();
+        ForecastError::json(response).await
+    }
+
+    #[tokio::test]
+    async fn json_body() {
+        let body = json(MockResponse::ok(r#"{"value":1}"#)).await.unwrap();
+        assert_eq!(body, Body { value: 1 });
+    }
+
+    #[tokio::test]
+    async fn json_error_status() {
+        let error = json(MockResponse::status(404)).await.unwrap_err();
+        let ForecastError::Status { status: 404, url } = error else {
+            panic!("expected a status error, got {error:?}");
+        };
+        assert!(url.ends_with("/forecast"), "{url}");
+    }
+
+    #[tokio::test]
+    async fn json_decode_error() {
+        let body = format!(r#"{{"padding":"{}","value":"one"}}"#, "x".repeat(100));
+        let error = json(MockResponse::ok(body)).await.unwrap_err();
+        let ForecastError::Decode { error, payload } = error else {
+            panic!("expected a decode error, got {error:?}");
+        };
+        assert!(error.contains("invalid type"), "{error}");
+        // Only the part around the error is kept of the long line
+        assert!(payload.chars().count() <= PAYLOAD_CONTEXT, "{payload}");
+        assert!(!payload.contains(&"x".repeat(100)), "{payload}");
+        assert!(payload.contains(r#""value":"one"#), "{payload}");
+    }
+}
@@ -0,0 +1,331 @@
+use std::fmt::Write;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use chrono::{DateTime, Local, NaiveDate};
+use serde::Serialize;
+
+use crate::app::config::WeatherConfig;
+use crate::model::weather::{DailyForecast, HourlyForecast, WeatherData};
+
+/// File format of a forecast export.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ExportFormat {
+    Json,
+    Csv,
+}
+
+impl ExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Csv => "csv",
+        }
+    }
+}
+
+impl FromStr for ExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "json" => Ok(Self::Json),
+            "csv" => Ok(Self::Csv),
+            _ => Err(format!("unknown export format {s:?}, expected json or csv")),
+        }
+    }
+}
+
+/// Units the exported values are converted to.
+#[derive(Debug, Serialize)]
+struct ExportUnits {
+    temperature: &'static str,
+    pressure: &'static str,
+    speed: &'static str,
+    precipitation: &'static str,
+    percentage: &'static str,
+    direction: &'static str,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportHourly {
+    start: DateTime<Local>,
+    end: DateTime<Local>,
+    symbol: Option<String>,
+    air_temperature: Option<f64>,
+    apparent_temperature: Option<f64>,
+    probability_of_precipitation: Option<f64>,
+    precipitation_amount: Option<f64>,
+    precipitation_amount_min: Option<f64>,
+    precipitation_amount_max: Option<f64>,
+    relative_humidity: Option<f64>,
+    cloud_area_fraction: Option<f64>,
+    air_pressure_at_sea_level: Option<f64>,
+    wind_speed: Option<f64>,
+    wind_from_direction: Option<f64>,
+    ultraviolet_index_clear_sky: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct ExportDaily {
+    date: NaiveDate,
+    symbol: Option<String>,
+    air_temperature_max: Option<f64>,
+    air_temperature_min: Option<f64>,
+    probability_of_precipitation: Option<f64>,
+    precipitation_amount: Option<f64>,
+    precipitation_amount_min: Option<f64>,
+    precipitation_amount_max: Option<f64>,
+    wind_speed_max: Option<f64>,
+    wind_speed_of_gust_max: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+struct Export<'a> {
+    location: Option<&'a str>,
+    latitude: f64,
+    longitude: f64,
+    provider: String,
+    attribution: String,
+    updated_at: DateTime<Local>,
+    units: ExportUnits,
+    hourly: Vec<ExportHourly>,
+    daily: Vec<ExportDaily>,
+}
+
+/// Round converted values so exports do not carry conversion noise.
+fn round(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+impl ExportHourly {
+    fn new(config: &WeatherConfig, forecast: &HourlyForecast) -> Self {
+        let conditions = &forecast.conditions;
+        Self {
+            start: forecast.interval.start,
+            end: forecast.interval.end,
+            symbol: forecast.symbol.map(|symbol| symbol.code()),
+            air_temperature: conditions
+                .air_temperature
+                .map(|value| round(config.units.convert(value))),
+            apparent_temperature: conditions
+                .apparent_temperature
+                .map(|value| round(config.units.convert(value))),
+            probability_of_precipitation: forecast
+                .probability_of_precipitation
+                .map(|value| value.0),
+            precipitation_amount: forecast
+                .precipitation_amount
+                .map(|value| round(config.precipitation_units.convert(value))),
+            precipitation_amount_min: forecast
+                .precipitation_window()
+                .and_then(|window| window.amount_min)
+                .map(|value| round(config.precipitation_units.convert(value))),
+            precipitation_amount_max: forecast
+                .precipitation_window()
+                .and_then(|window| window.amount_max)
+                .map(|value| round(config.precipitation_units.convert(value))),
+            relative_humidity: conditions.relative_humidity.map(|value| value.0),
+            cloud_area_fraction: conditions.cloud_area_fraction.map(|value| value.0),
+            air_pressure_at_sea_level: conditions
+                .air_pressure_at_sea_level
+                .map(|value| round(config.pressure_units.convert(value))),
+            wind_speed: conditions
+                .wind_speed
+                .map(|value| round(config.speed_units.convert(value))),
+            wind_from_direction: conditions.wind_from_direction.map(|value| value.0),
+            ultraviolet_index_clear_sky: conditions.ultraviolet_index_clear_sky,
+        }
+    }
+}
+
+impl ExportDaily {
+    fn new(config: &WeatherConfig, forecast: &DailyForecast) -> Self {
+        Self {
+            date: forecast.date,
+            symbol: forecast.symbol.map(|symbol| symbol.code()),
+            air_temperature_max: forecast
+                .air_temperature_max
+                .map(|value| round(config.units.convert(value))),
+            air_temperature_min: forecast
+                .air_temperature_min
+                .map(|value| round(config.units.convert(value))),
+            probability_of_precipitation: forecast
+                .probability_of_precipitation
+                .map(|value| value.0),
+            precipitation_amount: forecast
+                .precipitation_amount
+                .map(|value| round(config.precipitation_units.convert(value))),
+            precipitation_amount_min: forecast
+                .precipitation_amount_min
+                .map(|value| round(config.precipitation_units.convert(value))),
+            precipitation_amount_max: forecast
+                .precipitation_amount_max
+                .map(|value| round(config.precipitation_units.convert(value))),
+            wind_speed_max: forecast
+                .wind_speed_max
+                .map(|value| round(config.speed_units.convert(value))),
+            wind_speed_of_gust_max: forecast
+                .wind_speed_of_gust_max
+                .map(|value| round(config.speed_units.convert(value))),
+        }
+    }
+}
+
+impl<'a> Export<'a> {
+    fn new(config: &'a WeatherConfig, weather_data: &WeatherData) -> Self {
+        let (latitude, longitude) = weather_data.coordinates;
+        Self {
+            location: config.location().map(|location| location.name.as_str()),
+            latitude,
+            longitude,
+            provider: config.provider.name(),
+            attribution: config.provider.attribution(),
+            updated_at: weather_data.updated_at,
+            units: ExportUnits {
+                temperature: config.units.label(),
+                pressure: config.pressure_units.label(),
+                speed: config.speed_units.label(),
+                precipitation: config.precipitation_units.label(),
+                percentage: "%",
+                direction: "°",
+            },
+            hourly: weather_data
+                .hourly
+                .iter()
+                .map(|forecast| ExportHourly::new(config, forecast))
+                .collect(),
+            daily: weather_data
+                .daily
+                .iter()
+                .map(|forecast| ExportDaily::new(config, forecast))
+                .collect(),
+        }
+    }
+}
+
+/// Serialize the forecast of the selected location in the configured units.
+///
+/// JSON contains the hourly and daily forecasts, CSV only the hourly timeseries
+/// with the units in the header.
+pub fn export(config: &WeatherConfig, weather_data: &WeatherData, format: ExportFormat) -> String {
+    let export = Export::new(config, weather_data);
+    match format {
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(&export).expect("forecast export is valid JSON")
+        }
+        ExportFormat::Csv => export.to_csv(),
+    }
+}
+
+/// Write `contents` to `name.extension` in `directory`, returning the path
+/// written.
+///
+/// Existing files are kept, the export is written to `name-2.extension`,
+/// `name-3.extension` and so on instead.
+pub fn write_new(
+    directory: &Path,
+    name: &str,
+    extension: &str,
+    contents: &str,
+) -> std::io::Result<PathBuf> {
+    let mut number = 1;
+    loop {
+        let file_name = match number {
+            1 => format!("{name}.{extension}"),
+            number => format!("{name}-{number}.{extension}"),
+        };
+        let path = directory.join(file_name);
+        match std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&path)
+        {
+            Ok(mut file) => {
+                std::io::Write::write_all(&mut file, contents.as_bytes())?;
+                return Ok(path);
+            }
+            Err(err) if err.kind() == ErrorKind::AlreadyExists => number += 1,
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+impl Export<'_> {
+    fn to_csv(&self) -> String {
+        let units = &self.units;
+        let mut csv = format!(
+            "start,end,symbol,air_temperature ({}),apparent_temperature ({}),\
+             probability_of_precipitation (%),precipitation_amount ({}),\
+             precipitation_amount_min ({}),precipitation_amount_max ({}),relative_humidity (%),\
+             cloud_area_fraction (%),air_pressure_at_sea_level ({}),wind_speed ({}),\
+             wind_from_direction (°),ultraviolet_index_clear_sky\n",
+            units.temperature,
+            units.temperature,
+            units.precipitation,
+            units.precipitation,
+            units.precipitation,
+            units.pressure,
+            units.speed
+        );
+
+        fn field(value: Option<f64>) -> String {
+            value.map(|value| value.to_string()).unwrap_or_default()
+        }
+
+        for row in &self.hourly {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                row.start.to_rfc3339(),
+                row.end.to_rfc3339(),
+                row.symbol.as_deref().unwrap_or_default(),
+                field(row.air_temperature),
+                field(row.apparent_temperature),
+                field(row.probability_of_precipitation),
+                field(row.precipitation_amount),
+                field(row.precipitation_amount_min),
+                field(row.precipitation_amount_max),
+                field(row.relative_humidity),
+                field(row.cloud_area_fraction),
+                field(row.air_pressure_at_sea_level),
+                field(row.wind_speed),
+                field(row.wind_from_direction),
+                field(row.ultraviolet_index_clear_sky),
+            );
+        }
+
+        csv
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_without_replacing() {
+        let directory =
+            std::env::temp_dir().join(format!("forecast-export-{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let paths: Vec<PathBuf> = ["first", "second", "third"]
+            .into_iter()
+            .map(|contents| write_new(&directory, "forecast", "json", contents).unwrap())
+            .collect();
+
+        assert_eq!(
+            paths,
+            [
+                directory.join("forecast.json"),
+                directory.join("forecast-2.json"),
+                directory.join("forecast-3.json"),
+            ]
+        );
+        assert_eq!(std::fs::read_to_string(&paths[0]).unwrap(), "first");
+        assert_eq!(std::fs::read_to_string(&paths[2]).unwrap(), "third");
+
+        std::fs::remove_dir_all(directory).unwrap();
+    }
+}
@@ -0,0 +1,357 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use reqwest::header::RETRY_AFTER;
+use reqwest::{RequestBuilder, Response, StatusCode};
+
+use crate::model::error::ForecastError;
+
+/// Number of retries after the first attempt.
+const MAX_RETRIES: u32 = 3;
+/// Delay before the first retry, doubled for each further retry.
+const INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+/// Longest delay before a retry. Servers asking for longer get the error.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Minimum time between two requests to a host, following its usage policy.
+///
+/// met.no allows 20 requests per second per application, the free
+/// geocode.maps.co plan one per second.
+const RATE_LIMITS: &[(&str, Duration)] = &[
+    ("api.met.no", Duration::from_millis(50)),
+    ("geocode.maps.co", Duration::from_secs(1)),
+];
+
+/// Settings of the client shared by the forecast providers and the geocoder.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HttpSettings {
+    pub timeout: Duration,
+    /// Contact email appended to the User-Agent, as the met.no terms of
+    /// service ask of applications with many users.
+    pub contact: Option<String>,
+    /// Proxy for all requests, e.g. `http://proxy:3128` or `socks5://proxy:1080`.
+    ///
+    /// Without one the `HTTP_PROXY`, `HTTPS_PROXY` and `NO_PROXY` environment
+    /// variables are used.
+    pub proxy: Option<String>,
+    /// Comma separated hosts and domains reached without the proxy.
+    pub no_proxy: Option<String>,
+    /// PEM file with root certificates trusted in addition to the system ones.
+    pub ca_certificates: Option<PathBuf>,
+    /// Fail all requests with [`ForecastError::Offline`] without sending them.
+    pub offline: bool,
+}
+
+impl Default for HttpSettings {
+    fn default() -> Self {
+        Self {
+            timeout: Duration::from_secs(30),
+            contact: None,
+            proxy: None,
+            no_proxy: None,
+            ca_certificates: None,
+            offline: false,
+        }
+    }
+}
+
+impl HttpSettings {
+    /// E.g. `cosmic-ext-forecast/0.1.0 (+https://github.com/cosmic-utils/forecast; me@example.com)`.
+    pub fn user_agent(&self) -> String {
+        let mut comment = format!("+{}", env!("CARGO_PKG_REPOSITORY"));
+        if let Some(contact) = &self.contact {
+            comment.push_str("; ");
+            comment.push_str(contact);
+        }
+        format!(
+            "{}/{} ({comment})",
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        )
+    }
+}
+
+struct Shared {
+    settings: HttpSettings,
+    client: reqwest::Client,
+}
+
+fn shared() -> &'static RwLock<Shared> {
+    static SHARED: OnceLock<RwLock<Shared>> = OnceLock::new();
+    SHARED.get_or_init(|| {
+        let settings = HttpSettings::default();
+        RwLock::new(Shared {
+            client: build_client(&settings).unwrap_or_default(),
+            settings,
+        })
+    })
+}
+
+fn build_client(settings: &HttpSettings) -> Result<reqwest::Client, ForecastError> {
+    let invalid = |err: &dyn std::fmt::Display| ForecastError::NetworkSettings(err.to_string());
+
+    let mut builder = reqwest::Client::builder()
+        .user_agent(settings.user_agent())
+        .timeout(settings.timeout);
+
+    if let Some(proxy) = &settings.proxy {
+        let no_proxy = settings
+            .no_proxy
+            .as_deref()
+            .and_then(reqwest::NoProxy::from_string);
+        builder = builder.proxy(
+            reqwest::Proxy::all(proxy)
+                .map_err(|err| invalid(&err))?
+                .no_proxy(no_proxy),
+        );
+    }
+
+    if let Some(path) = &settings.ca_certificates {
+        let pem = std::fs::read(path)
+            .map_err(|err| invalid(&format_args!("{}: {err}", path.display())))?;
+        for certificate in
+            reqwest::Certificate::from_pem_bundle(&pem).map_err(|err| invalid(&err))?
+        {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    builder.build().map_err(|err| invalid(&err))
+}
+
+/// Rebuild the shared client if `settings` changed.
+///
+/// If the settings are invalid, requests are sent without proxy and custom
+/// certificates until they are fixed.
+pub fn configure(settings: HttpSettings) -> Result<(), ForecastError> {
+    let mut shared = shared().write().unwrap();
+    if shared.settings == settings {
+        return Ok(());
+    }
+
+    let client = build_client(&settings);
+    shared.settings = settings;
+    match client {
+        Ok(client) => {
+            shared.client = client;
+            Ok(())
+        }
+        Err(err) => {
+            shared.client = build_client(&HttpSettings {
+                proxy: None,
+                no_proxy: None,
+                ca_certificates: None,
+                ..shared.settings.clone()
+            })
+            .unwrap_or_default();
+            Err(err)
+        }
+    }
+}
+
+/// The shared client. It is behind an `Arc`, so cloning it is cheap.
+pub fn client() -> reqwest::Client {
+    shared().read().unwrap().client.clone()
+}
+
+/// Send `request`, waiting for the rate limit of its host and retrying on
+/// `429 Too Many Requests`, `5xx` statuses, timeouts and connection errors.
+pub async fn send(request: RequestBuilder) -> Result<Response, ForecastError> {
+    if shared().read().unwrap().settings.offline {
+        return Err(ForecastError::Offline);
+    }
+
+    let (client, request) = request.build_split();
+    let request = request?;
+    let host = request.url().host_str().unwrap_or_default().to_string();
+
+    let mut attempt = 0;
+    loop {
+        wait_for_rate_limit(&host).await;
+
+        let retry_request = request.try_clone();
+        let result = client
+            .execute(retry_request.expect("requests without streaming body can be cloned"))
+            .await;
+
+        let delay = match &result {
+            Ok(response) if is_retryable_status(response.status()) => retry_after(response),
+            Ok(_) => return Ok(result?),
+            Err(err) if err.is_timeout() || err.is_connect() => None,
+            Err(_) => return Ok(result?),
+        }
+        .unwrap_or(INITIAL_BACKOFF * 2u32.pow(attempt));
+
+        if attempt >= MAX_RETRIES || delay > MAX_BACKOFF {
+            return Ok(result?);
+        }
+
+        log::info!("retrying request to {} in {:?}", host, delay);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+/// `Retry-After` header, either in seconds or as an HTTP date.
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(RETRY_AFTER)?.to_str().ok()?;
+    if let Ok(seconds) = value.parse() {
+        return Some(Duration::from_secs(seconds));
+    }
+    let date = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    (date.to_utc() - chrono::Utc::now()).to_std().ok()
+}
+
+/// Wait until a request to `host` is allowed by [`RATE_LIMITS`].
+async fn wait_for_rate_limit(host: &str) {
+    static NEXT_REQUEST: OnceLock<Mutex<HashMap<String, Instant>>> = OnceLock::new();
+
+    let Some(&(_, interval)) = RATE_LIMITS.iter().find(|(limited, _)| *limited == host) else {
+        return;
+    };
+
+    // Reserve a slot while holding the lock, then wait outside of it
+    let slot = {
+        let mut next_request = NEXT_REQUEST.get_or_init(Default::default).lock().unwrap();
+        let now = Instant::now();
+        let slot = next_request.get(host).copied().unwrap_or(now).max(now);
+        next_request.insert(host.to_string(), slot + interval);
+        slot
+    };
+
+    tokio::time::sleep_until(slot.into()).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+    use crate::model::mock_server::{MockResponse, MockServer};
+
+    /// Server answering the n-th request with `responses[n]`, and the last one
+    /// after that.
+    fn replay(responses: Vec<MockResponse>) -> MockServer {
+        let count = AtomicUsize::new(0);
+        MockServer::start(move |_| {
+            let index = count.fetch_add(1, Ordering::SeqCst);
+            responses[index.min(responses.len() - 1)].clone()
+        })
+    }
+
+    fn gaps(server: &MockServer) -> Vec<Duration> {
+        server
+            .requests()
+            .windows(2)
+            .map(|pair| pair[1].received - pair[0].received)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn retry_after_too_many_requests() {
+        let server = replay(vec![
+            MockResponse::status(429).header("Retry-After", "1"),
+            MockResponse::ok("{}"),
+        ]);
+
+        let response = send(client().get(server.url("/"))).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let gaps = gaps(&server);
+        assert_eq!(gaps.len(), 1);
+        assert!(gaps[0] >= Duration::from_secs(1), "{gaps:?}");
+    }
+
+    #[tokio::test]
+    async fn exponential_backoff() {
+        let server = replay(vec![
+            MockResponse::status(503),
+            MockResponse::status(502),
+            MockResponse::ok("{}"),
+        ]);
+
+        let response = send(client().get(server.url("/"))).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        let gaps = gaps(&server);
+        assert_eq!(gaps.len(), 2);
+        assert!(gaps[0] >= INITIAL_BACKOFF, "{gaps:?}");
+        assert!(gaps[1] >= INITIAL_BACKOFF * 2, "{gaps:?}");
+    }
+
+    #[tokio::test]
+    async fn retry_after_above_maximum() {
+        let server = replay(vec![
+            MockResponse::status(429).header("Retry-After", "120"),
+            MockResponse::ok("{}"),
+        ]);
+
+        let response = send(client().get(server.url("/"))).await.unwrap();
+
+        // Waiting that long is left to the next refresh
+        assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn give_up_after_maximum_retries() {
+        let server = replay(vec![MockResponse::status(503).header("Retry-After", "0")]);
+
+        let response = send(client().get(server.url("/"))).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(server.requests().len(), MAX_RETRIES as usize + 1);
+    }
+
+    #[tokio::test]
+    async fn no_retry_on_client_errors() {
+        let server = replay(vec![MockResponse::status(404), MockResponse::ok("{}")]);
+
+        let response = send(client().get(server.url("/"))).await.unwrap();
+
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(server.requests().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn rate_limit_per_host() {
+        let server = replay(vec![MockResponse::ok("{}")]);
+        // Send requests for a rate limited host to the mock server
+        let client = reqwest::Client::builder()
+            .resolve("api.met.no", server.address())
+            .build()
+            .unwrap();
+        let url = format!("http://api.met.no:{}/", server.address().port());
+        let (_, interval) = RATE_LIMITS
+            .iter()
+            .find(|(host, _)| *host == "api.met.no")
+            .unwrap();
+
+        let start = Instant::now();
+        for _ in 0..5 {
+            send(client.get(&url)).await.unwrap();
+        }
+
+        assert_eq!(server.requests().len(), 5);
+        assert!(start.elapsed() >= *interval * 4, "{:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn no_rate_limit_for_other_hosts() {
+        let server = replay(vec![MockResponse::ok("{}")]);
+        let start = Instant::now();
+
+        for _ in 0..20 {
+            send(client().get(server.url("/"))).await.unwrap();
+        }
+
+        assert_eq!(server.requests().len(), 20);
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
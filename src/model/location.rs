@@ -2,6 +2,9 @@ use std::convert::AsRef;
 
 use serde::{Deserialize, Serialize};
 
+use crate::model::error::ForecastError;
+use crate::model::http;
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Location {
     place_id: u64,
@@ -22,16 +25,22 @@ impl AsRef<str> for Location {
 }
 
 impl Location {
-    pub async fn get_location_data(query: String) -> Result<Vec<Location>, reqwest::Error> {
-        let params = [("q", query)];
-
-        let geocoding_ans: Vec<Location> = reqwest::Client::new()
-            .get("https://geocode.maps.co/search?".to_string())
-            .query(&params)
-            .send()
-            .await?
-            .json()
-            .await?;
+    pub async fn get_location_data(query: String) -> Result<Vec<Location>, ForecastError> {
+        let params = [("q", query.clone())];
+
+        let geocoding_ans: Vec<Location> = ForecastError::json(
+            http::send(
+                http::client()
+                    .get("https://geocode.maps.co/search?".to_string())
+                    .query(&params),
+            )
+            .await?,
+        )
+        .await?;
+
+        if geocoding_ans.is_empty() {
+            return Err(ForecastError::LocationNotFound(query));
+        }
 
         Ok(geocoding_ans)
     }
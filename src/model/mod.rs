@@ -1,2 +1,14 @@
+pub mod archive;
+pub mod cache;
+pub mod changes;
+pub mod error;
+pub mod export;
+pub mod http;
 pub mod location;
+#[cfg(test)]
+pub mod mock_server;
+pub mod provider;
+pub mod quantity;
+pub mod symbol;
+pub mod timezone;
 pub mod weather;
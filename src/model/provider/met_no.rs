@@ -0,0 +1,312 @@
+use std::future::Future;
+
+use chrono::{DateTime, Duration, Local};
+use serde::Deserialize;
+
+use crate::app::config::ForecastState;
+use crate::fl;
+use crate::model::error::ForecastError;
+use crate::model::http;
+use crate::model::quantity::{Bearing, Percentage, Precipitation, Pressure, Speed, Temperature};
+use crate::model::symbol::WeatherSymbol;
+use crate::model::timezone;
+use crate::model::weather::{
+    Conditions, DailyForecast, HourlyForecast, Interval, PrecipitationWindow, WeatherData,
+    WeatherRequestStatus,
+};
+
+use super::{Field, WeatherProvider};
+
+const FORECAST_URL: &str = "https://api.met.no/weatherapi/locationforecast/2.0/complete?";
+
+/// Locationforecast 2.0 from the Norwegian Meteorological Institute.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MetNo;
+
+#[derive(Debug, Deserialize)]
+struct Geometry {
+    coordinates: Vec<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Meta {
+    updated_at: DateTime<Local>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct Details {
+    air_pressure_at_sea_level: Option<f64>,
+    air_temperature: Option<f64>,
+    air_temperature_max: Option<f64>,
+    air_temperature_min: Option<f64>,
+    cloud_area_fraction: Option<f64>,
+    cloud_area_fraction_high: Option<f64>,
+    cloud_area_fraction_low: Option<f64>,
+    cloud_area_fraction_medium: Option<f64>,
+    dew_point_temperature: Option<f64>,
+    fog_area_fraction: Option<f64>,
+    precipitation_amount: Option<f64>,
+    precipitation_amount_max: Option<f64>,
+    precipitation_amount_min: Option<f64>,
+    probability_of_precipitation: Option<f64>,
+    relative_humidity: Option<f64>,
+    ultraviolet_index_clear_sky: Option<f64>,
+    wind_from_direction: Option<f64>,
+    wind_speed: Option<f64>,
+    wind_speed_of_gust: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Summary {
+    symbol_code: String,
+}
+
+/// Summary and details of the `next_1_hours`, `next_6_hours` and
+/// `next_12_hours` periods.
+#[derive(Debug, Deserialize)]
+struct Period {
+    summary: Summary,
+    details: Option<Details>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Instant {
+    details: Details,
+}
+
+#[derive(Debug, Deserialize)]
+struct Data {
+    instant: Instant,
+    next_12_hours: Option<Period>,
+    next_1_hours: Option<Period>,
+    next_6_hours: Option<Period>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Timeseries {
+    time: DateTime<Local>,
+    data: Data,
+}
+
+#[derive(Debug, Deserialize)]
+struct Properties {
+    meta: Meta,
+    timeseries: Vec<Timeseries>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    geometry: Geometry,
+    properties: Properties,
+}
+
+impl WeatherProvider for MetNo {
+    fn attribution(&self) -> String {
+        fl!("attribution-met-no")
+    }
+
+    fn supported_fields(&self) -> &'static [Field] {
+        &[
+            Field::AirPressure,
+            Field::AirTemperature,
+            Field::AirTemperatureMax,
+            Field::AirTemperatureMin,
+            Field::CloudAreaFraction,
+            Field::CloudAreaFractionLayers,
+            Field::DewPointTemperature,
+            Field::FogAreaFraction,
+            Field::PrecipitationAmount,
+            Field::ProbabilityOfPrecipitation,
+            Field::RelativeHumidity,
+            Field::UltravioletIndex,
+            Field::WindFromDirection,
+            Field::WindSpeed,
+            Field::WindSpeedOfGust,
+            Field::SymbolCode,
+        ]
+    }
+
+    fn get_weather_data(
+        &self,
+        coords: (f64, f64),
+        last_request: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl Future<Output = Result<(ForecastState, WeatherRequestStatus), ForecastError>>
+           + Send
+           + 'static {
+        async move {
+            let mut status = WeatherRequestStatus::Other;
+            let query_params = [("lat", coords.0), ("lon", coords.1)];
+
+            let mut res = http::client().get(FORECAST_URL);
+            if let Some(last_request) = last_request {
+                let format_str = "%a, %d %b %Y %H:%M:%S GMT";
+                res = res.header(
+                    "If-Modified-Since",
+                    last_request.format(format_str).to_string(),
+                )
+            }
+            let res = http::send(res.query(&query_params)).await?;
+
+            let get_header_date = |key: &str| {
+                res.headers().get(key).and_then(|date| {
+                    Some(
+                        chrono::DateTime::<chrono::FixedOffset>::parse_from_rfc2822(
+                            date.to_str().ok()?,
+                        )
+                        .ok()?,
+                    )
+                })
+            };
+
+            let expires = get_header_date("Expires");
+
+            let last_request: chrono::DateTime<chrono::FixedOffset> = chrono::Utc::now().into();
+
+            let weather_ans = if res.status().as_u16() == 304 {
+                status = WeatherRequestStatus::NotModified;
+                None
+            } else {
+                let weather_ans: Response = ForecastError::json(res).await?;
+                Some(weather_ans.into_weather_data())
+            };
+
+            let forecast_state = ForecastState {
+                last_request: Some(last_request),
+                expires,
+                weather_data: weather_ans,
+                gridpoint: None,
+                refresh_failed: false,
+            };
+            Ok((forecast_state, status))
+        }
+    }
+}
+
+impl Response {
+    fn into_weather_data(self) -> WeatherData {
+        let timeseries = &self.properties.timeseries;
+
+        let hourly: Vec<HourlyForecast> = timeseries.iter().map(Timeseries::hourly).collect();
+
+        let coordinates = (
+            self.geometry
+                .coordinates
+                .get(1)
+                .copied()
+                .unwrap_or_default(),
+            self.geometry
+                .coordinates
+                .first()
+                .copied()
+                .unwrap_or_default(),
+        );
+
+        // met.no sends UTC times, days are those of the location's calendar as
+        // with the other providers, whatever timezone times are shown in
+        let daily = match timezone::at(coordinates) {
+            Some(timezone) => DailyForecast::aggregate(&hourly, &timezone),
+            None => DailyForecast::aggregate(&hourly, &Local),
+        };
+
+        WeatherData {
+            updated_at: self.properties.meta.updated_at,
+            coordinates,
+            current: hourly.first().cloned(),
+            hourly,
+            daily,
+        }
+    }
+}
+
+impl Timeseries {
+    /// The shortest period available, met.no drops `next_1_hours` a few days ahead.
+    fn period(&self) -> Option<(&Period, Duration)> {
+        let data = &self.data;
+        data.next_1_hours
+            .as_ref()
+            .map(|period| (period, Duration::hours(1)))
+            .or_else(|| {
+                data.next_6_hours
+                    .as_ref()
+                    .map(|period| (period, Duration::hours(6)))
+            })
+            .or_else(|| {
+                data.next_12_hours
+                    .as_ref()
+                    .map(|period| (period, Duration::hours(12)))
+            })
+    }
+
+    fn hourly(&self) -> HourlyForecast {
+        let period = self.period();
+        let duration = period.map_or(Duration::hours(1), |(_, duration)| duration);
+        let period_details = period.and_then(|(period, _)| period.details.as_ref());
+
+        HourlyForecast {
+            interval: Interval {
+                start: self.time,
+                end: self.time + duration,
+            },
+            conditions: self.data.instant.details.conditions(),
+            symbol: period
+                .map(|(period, _)| WeatherSymbol::parse_or_unknown(&period.summary.symbol_code)),
+            probability_of_precipitation: period_details
+                .and_then(|details| details.probability_of_precipitation)
+                .map(Percentage),
+            precipitation_amount: period_details
+                .and_then(|details| details.precipitation_amount)
+                .map(Precipitation),
+            air_temperature_max: period_details
+                .and_then(|details| details.air_temperature_max)
+                .map(Temperature),
+            air_temperature_min: period_details
+                .and_then(|details| details.air_temperature_min)
+                .map(Temperature),
+            precipitation_windows: self.precipitation_windows(),
+        }
+    }
+
+    /// Precipitation of every period with details, shortest first.
+    fn precipitation_windows(&self) -> Vec<PrecipitationWindow> {
+        let data = &self.data;
+        [
+            (&data.next_1_hours, 1),
+            (&data.next_6_hours, 6),
+            (&data.next_12_hours, 12),
+        ]
+        .into_iter()
+        .filter_map(|(period, hours)| {
+            let details = period.as_ref()?.details.as_ref()?;
+            Some(PrecipitationWindow {
+                hours,
+                amount: details.precipitation_amount.map(Precipitation),
+                amount_min: details.precipitation_amount_min.map(Precipitation),
+                amount_max: details.precipitation_amount_max.map(Precipitation),
+                probability: details.probability_of_precipitation.map(Percentage),
+            })
+        })
+        .collect()
+    }
+}
+
+impl Details {
+    fn conditions(&self) -> Conditions {
+        Conditions {
+            air_pressure_at_sea_level: self.air_pressure_at_sea_level.map(Pressure),
+            air_temperature: self.air_temperature.map(Temperature),
+            apparent_temperature: None,
+            cloud_area_fraction: self.cloud_area_fraction.map(Percentage),
+            cloud_area_fraction_low: self.cloud_area_fraction_low.map(Percentage),
+            cloud_area_fraction_medium: self.cloud_area_fraction_medium.map(Percentage),
+            cloud_area_fraction_high: self.cloud_area_fraction_high.map(Percentage),
+            dew_point_temperature: self.dew_point_temperature.map(Temperature),
+            fog_area_fraction: self.fog_area_fraction.map(Percentage),
+            relative_humidity: self.relative_humidity.map(Percentage),
+            ultraviolet_index_clear_sky: self.ultraviolet_index_clear_sky,
+            visibility: None,
+            wind_from_direction: self.wind_from_direction.map(Bearing),
+            wind_speed: self.wind_speed.map(Speed),
+            wind_speed_of_gust: self.wind_speed_of_gust.map(Speed),
+        }
+    }
+}
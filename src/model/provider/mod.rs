@@ -0,0 +1,141 @@
+use std::future::Future;
+
+use serde::{Deserialize, Serialize};
+
+use crate::app::config::ForecastState;
+use crate::fl;
+use crate::model::error::ForecastError;
+use crate::model::weather::WeatherRequestStatus;
+
+pub mod met_no;
+pub mod nws;
+pub mod open_meteo;
+
+use met_no::MetNo;
+use nws::{Gridpoint, Nws};
+use open_meteo::OpenMeteo;
+
+/// A forecast value a provider is able to fill in.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Field {
+    AirPressure,
+    AirTemperature,
+    AirTemperatureMax,
+    AirTemperatureMin,
+    ApparentTemperature,
+    CloudAreaFraction,
+    CloudAreaFractionLayers,
+    DewPointTemperature,
+    FogAreaFraction,
+    PrecipitationAmount,
+    ProbabilityOfPrecipitation,
+    RelativeHumidity,
+    UltravioletIndex,
+    Visibility,
+    WindFromDirection,
+    WindSpeed,
+    WindSpeedOfGust,
+    SymbolCode,
+}
+
+/// A source of forecast data.
+///
+/// Implementations fetch a forecast for a pair of coordinates and convert it
+/// into [`WeatherData`](crate::model::weather::WeatherData), so the views never
+/// depend on a particular vendor.
+pub trait WeatherProvider {
+    /// Attribution that must be shown next to the data.
+    fn attribution(&self) -> String;
+
+    /// Fields this provider returns. Anything else is left empty.
+    fn supported_fields(&self) -> &'static [Field];
+
+    /// Fetch the forecast for `coords` (latitude, longitude).
+    ///
+    /// `last_request` is the date of the previous successful request, which
+    /// providers may use for conditional requests.
+    fn get_weather_data(
+        &self,
+        coords: (f64, f64),
+        last_request: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl Future<Output = Result<(ForecastState, WeatherRequestStatus), ForecastError>>
+           + Send
+           + 'static;
+
+    fn supports(&self, field: Field) -> bool {
+        self.supported_fields().contains(&field)
+    }
+}
+
+/// Provider selected in the settings.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum Provider {
+    #[default]
+    MetNo,
+    OpenMeteo,
+    Nws,
+}
+
+impl Provider {
+    pub fn all() -> &'static [Self] {
+        &[Self::MetNo, Self::OpenMeteo, Self::Nws]
+    }
+
+    /// Stable identifier used in cache file names.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Self::MetNo => "met-no",
+            Self::OpenMeteo => "open-meteo",
+            Self::Nws => "nws",
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            Self::MetNo => fl!("provider-met-no"),
+            Self::OpenMeteo => fl!("provider-open-meteo"),
+            Self::Nws => fl!("provider-nws"),
+        }
+    }
+
+    pub fn attribution(&self) -> String {
+        match self {
+            Self::MetNo => MetNo.attribution(),
+            Self::OpenMeteo => OpenMeteo::default().attribution(),
+            Self::Nws => Nws::default().attribution(),
+        }
+    }
+
+    pub fn supports(&self, field: Field) -> bool {
+        match self {
+            Self::MetNo => MetNo.supports(field),
+            Self::OpenMeteo => OpenMeteo::default().supports(field),
+            Self::Nws => Nws::default().supports(field),
+        }
+    }
+
+    /// `gridpoint` is the NWS grid cached from a previous request, if any.
+    pub async fn get_weather_data(
+        self,
+        coords: (f64, f64),
+        last_request: Option<chrono::DateTime<chrono::Utc>>,
+        gridpoint: Option<Gridpoint>,
+    ) -> Result<(ForecastState, WeatherRequestStatus), ForecastError> {
+        match self {
+            Self::MetNo => MetNo.get_weather_data(coords, last_request).await,
+            Self::OpenMeteo => {
+                OpenMeteo::default()
+                    .get_weather_data(coords, last_request)
+                    .await
+            }
+            Self::Nws => {
+                Nws {
+                    gridpoint,
+                    ..Default::default()
+                }
+                .get_weather_data(coords, last_request)
+                .await
+            }
+        }
+    }
+}
@@ -0,0 +1,628 @@
+use std::future::Future;
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::app::config::ForecastState;
+use crate::fl;
+use crate::model::error::ForecastError;
+use crate::model::http;
+use crate::model::quantity::{Bearing, Percentage, Speed, Temperature};
+use crate::model::symbol::{Variant, WeatherSymbol};
+use crate::model::weather::{
+    Conditions, DailyForecast, HourlyForecast, Interval, PrecipitationWindow, WeatherData,
+    WeatherRequestStatus,
+};
+
+use super::{Field, WeatherProvider};
+
+const API_URL: &str = "https://api.weather.gov";
+
+/// Forecast office grid resolved from a pair of coordinates.
+///
+/// Resolving the grid is a separate request, so the result is kept in
+/// [`ForecastState`] and reused for as long as the location stays the same.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Gridpoint {
+    pub latitude: f64,
+    pub longitude: f64,
+    pub forecast: String,
+    pub forecast_hourly: String,
+}
+
+impl Gridpoint {
+    fn matches(&self, coords: (f64, f64)) -> bool {
+        round(self.latitude) == round(coords.0) && round(self.longitude) == round(coords.1)
+    }
+}
+
+/// api.weather.gov from the US National Weather Service.
+#[derive(Clone, Debug)]
+pub struct Nws {
+    pub base_url: String,
+    pub gridpoint: Option<Gridpoint>,
+}
+
+impl Default for Nws {
+    fn default() -> Self {
+        Self {
+            base_url: API_URL.to_string(),
+            gridpoint: None,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct PointsResponse {
+    properties: PointsProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PointsProperties {
+    forecast: String,
+    forecast_hourly: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ForecastResponse {
+    properties: ForecastProperties,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ForecastProperties {
+    update_time: Option<DateTime<FixedOffset>>,
+    periods: Vec<Period>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Period {
+    start_time: DateTime<FixedOffset>,
+    end_time: DateTime<FixedOffset>,
+    is_daytime: bool,
+    temperature: Option<f64>,
+    temperature_unit: Option<String>,
+    probability_of_precipitation: Option<QuantitativeValue>,
+    relative_humidity: Option<QuantitativeValue>,
+    wind_speed: Option<String>,
+    wind_direction: Option<String>,
+    icon: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuantitativeValue {
+    value: Option<f64>,
+}
+
+impl WeatherProvider for Nws {
+    fn attribution(&self) -> String {
+        fl!("attribution-nws")
+    }
+
+    fn supported_fields(&self) -> &'static [Field] {
+        &[
+            Field::AirTemperature,
+            Field::AirTemperatureMax,
+            Field::AirTemperatureMin,
+            Field::ProbabilityOfPrecipitation,
+            Field::RelativeHumidity,
+            Field::WindFromDirection,
+            Field::WindSpeed,
+            Field::SymbolCode,
+        ]
+    }
+
+    fn get_weather_data(
+        &self,
+        coords: (f64, f64),
+        _last_request: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl Future<Output = Result<(ForecastState, WeatherRequestStatus), ForecastError>>
+           + Send
+           + 'static {
+        let base_url = self.base_url.clone();
+        let gridpoint = self.gridpoint.clone();
+        async move {
+            let client = http::client();
+
+            let gridpoint = match gridpoint.filter(|gridpoint| gridpoint.matches(coords)) {
+                Some(gridpoint) => gridpoint,
+                None => {
+                    let points: PointsResponse = ForecastError::json(
+                        http::send(client.get(format!(
+                            "{base_url}/points/{},{}",
+                            round(coords.0),
+                            round(coords.1)
+                        )))
+                        .await?,
+                    )
+                    .await?;
+                    Gridpoint {
+                        latitude: coords.0,
+                        longitude: coords.1,
+                        forecast: points.properties.forecast,
+                        forecast_hourly: points.properties.forecast_hourly,
+                    }
+                }
+            };
+
+            let hourly_res = http::send(
+                client
+                    .get(&gridpoint.forecast_hourly)
+                    .query(&[("units", "si")]),
+            )
+            .await?;
+
+            let expires = hourly_res
+                .headers()
+                .get("Expires")
+                .and_then(|date| DateTime::parse_from_rfc2822(date.to_str().ok()?).ok());
+
+            let hourly: ForecastResponse = ForecastError::json(hourly_res).await?;
+
+            let daily: ForecastResponse = ForecastError::json(
+                http::send(client.get(&gridpoint.forecast).query(&[("units", "si")])).await?,
+            )
+            .await?;
+
+            let last_request: DateTime<FixedOffset> = Utc::now().into();
+            let expires = expires.unwrap_or(last_request + chrono::Duration::hours(1));
+
+            let forecast_state = ForecastState {
+                last_request: Some(last_request),
+                expires: Some(expires),
+                weather_data: Some(into_weather_data(coords, hourly, daily)),
+                gridpoint: Some(gridpoint),
+                refresh_failed: false,
+            };
+            Ok((forecast_state, WeatherRequestStatus::Other))
+        }
+    }
+}
+
+fn into_weather_data(
+    coords: (f64, f64),
+    hourly: ForecastResponse,
+    daily: ForecastResponse,
+) -> WeatherData {
+    let hourly_forecast: Vec<HourlyForecast> = hourly
+        .properties
+        .periods
+        .iter()
+        .map(|period| HourlyForecast {
+            interval: period.interval(),
+            conditions: Conditions {
+                air_temperature: period.temperature(),
+                relative_humidity: period
+                    .relative_humidity
+                    .as_ref()
+                    .and_then(|humidity| humidity.value)
+                    .map(Percentage),
+                wind_from_direction: period
+                    .wind_direction
+                    .as_deref()
+                    .and_then(compass_to_degrees)
+                    .map(Bearing),
+                wind_speed: period.wind_speed.as_deref().and_then(wind_speed),
+                ..Default::default()
+            },
+            symbol: period.symbol(),
+            probability_of_precipitation: period.probability_of_precipitation(),
+            precipitation_amount: None,
+            air_temperature_max: None,
+            air_temperature_min: None,
+            precipitation_windows: vec![PrecipitationWindow {
+                hours: 1,
+                amount: None,
+                amount_min: None,
+                amount_max: None,
+                probability: period.probability_of_precipitation(),
+            }],
+        })
+        .collect();
+
+    // The daily forecast alternates between day and night periods
+    let mut daily_forecast: Vec<DailyForecast> = Vec::new();
+    for period in daily.properties.periods.iter() {
+        let date = period.start_time.date_naive();
+        if daily_forecast.last().map(|day| day.date) != Some(date) {
+            let start = period.start_time.with_timezone(&Local);
+            daily_forecast.push(DailyForecast {
+                date,
+                interval: Interval { start, end: start },
+                symbol: None,
+                air_temperature_max: None,
+                air_temperature_min: None,
+                probability_of_precipitation: None,
+                precipitation_amount: None,
+                precipitation_amount_min: None,
+                precipitation_amount_max: None,
+                wind_speed_max: None,
+                wind_speed_of_gust_max: None,
+            });
+        }
+        let Some(day) = daily_forecast.last_mut() else {
+            continue;
+        };
+
+        day.interval.end = period.end_time.with_timezone(&Local);
+        if period.is_daytime {
+            day.air_temperature_max = period.temperature();
+            day.symbol = period.symbol();
+        } else {
+            day.air_temperature_min = period.temperature();
+            day.symbol = day.symbol.take().or_else(|| period.symbol());
+        }
+        day.probability_of_precipitation = match (
+            day.probability_of_precipitation,
+            period.probability_of_precipitation(),
+        ) {
+            (Some(a), Some(b)) => Some(Percentage(a.0.max(b.0))),
+            (a, b) => a.or(b),
+        };
+    }
+
+    let now = Local::now();
+    WeatherData {
+        updated_at: hourly
+            .properties
+            .update_time
+            .map(|time| time.with_timezone(&Local))
+            .unwrap_or(now),
+        coordinates: coords,
+        current: hourly_forecast
+            .iter()
+            .find(|forecast| forecast.interval.contains(now))
+            .cloned(),
+        hourly: hourly_forecast,
+        daily: daily_forecast,
+    }
+}
+
+impl Period {
+    fn interval(&self) -> Interval {
+        Interval {
+            start: self.start_time.with_timezone(&Local),
+            end: self.end_time.with_timezone(&Local),
+        }
+    }
+
+    fn temperature(&self) -> Option<Temperature> {
+        let temperature = self.temperature?;
+        match self.temperature_unit.as_deref() {
+            Some("F") => Some(Temperature::from_fahrenheit(temperature)),
+            _ => Some(Temperature(temperature)),
+        }
+    }
+
+    fn probability_of_precipitation(&self) -> Option<Percentage> {
+        self.probability_of_precipitation
+            .as_ref()
+            .and_then(|probability| probability.value)
+            .map(Percentage)
+    }
+
+    /// Map the icon of the period, e.g. `.../icons/land/day/rain_showers,30?size=small`,
+    /// to the matching met.no symbol.
+    fn symbol(&self) -> Option<WeatherSymbol> {
+        let icon = self.icon.as_deref()?;
+        let path = icon.split('?').next()?;
+        // Periods that change conditions have two icons, e.g. `.../day/sct/rain,40`
+        let code = path.rsplit('/').next()?.split(',').next()?;
+        let variant = if self.is_daytime {
+            Variant::Day
+        } else {
+            Variant::Night
+        };
+
+        Some(match code {
+            "skc" | "hot" | "cold" => WeatherSymbol::ClearSky(variant),
+            "few" | "wind_skc" | "wind_few" => WeatherSymbol::Fair(variant),
+            "sct" | "wind_sct" => WeatherSymbol::PartlyCloudy(variant),
+            "bkn" | "ovc" | "wind_bkn" | "wind_ovc" | "dust" | "smoke" => WeatherSymbol::Cloudy,
+            "haze" | "fog" => WeatherSymbol::Fog,
+            "rain" => WeatherSymbol::Rain,
+            "rain_showers" | "rain_showers_hi" => WeatherSymbol::RainShowers(variant),
+            "tsra" | "tsra_sct" | "tsra_hi" => WeatherSymbol::RainAndThunder,
+            "tornado" | "hurricane" | "tropical_storm" => WeatherSymbol::HeavyRainAndThunder,
+            "snow" => WeatherSymbol::Snow,
+            "blizzard" => WeatherSymbol::HeavySnow,
+            "rain_snow" | "rain_sleet" | "snow_sleet" | "sleet" => WeatherSymbol::Sleet,
+            "fzra" | "rain_fzra" | "snow_fzra" => WeatherSymbol::Sleet,
+            _ => {
+                log::warn!("unknown NWS icon code {code}");
+                WeatherSymbol::Unknown
+            }
+        })
+    }
+}
+
+/// The points endpoint accepts at most four decimals.
+fn round(value: f64) -> f64 {
+    (value * 10_000.0).round() / 10_000.0
+}
+
+/// Parse wind speeds such as `10 mph`, `15 km/h` or `5 to 10 mph`,
+/// using the upper bound of a range.
+fn wind_speed(value: &str) -> Option<Speed> {
+    let speed = value
+        .split_whitespace()
+        .filter_map(|part| part.parse::<f64>().ok())
+        .reduce(f64::max)?;
+    if value.contains("mph") {
+        Some(Speed::from_miles_per_hour(speed))
+    } else if value.contains("km/h") {
+        Some(Speed::from_kilometres_per_hour(speed))
+    } else {
+        Some(Speed(speed))
+    }
+}
+
+fn compass_to_degrees(direction: &str) -> Option<f64> {
+    const POINTS: [&str; 16] = [
+        "N", "NNE", "NE", "ENE", "E", "ESE", "SE", "SSE", "S", "SSW", "SW", "WSW", "W", "WNW",
+        "NW", "NNW",
+    ];
+    POINTS
+        .iter()
+        .position(|point| *point == direction)
+        .map(|index| index as f64 * 22.5)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::model::mock_server::{MockResponse, MockServer};
+
+    /// Topeka, Kansas on the afternoon of 3 June 2024, in SI units.
+    const POINTS: &str = include_str!("fixtures/nws_points.json");
+    const FORECAST: &str = include_str!("fixtures/nws_forecast.json");
+    const FORECAST_HOURLY: &str = include_str!("fixtures/nws_forecast_hourly.json");
+
+    const COORDS: (f64, f64) = (39.7456, -97.0892);
+    const EXPIRES: &str = "Mon, 03 Jun 2024 19:45:00 GMT";
+
+    /// Serve the fixtures, with the forecast URLs of the points response
+    /// pointing back at the server.
+    fn start_server() -> MockServer {
+        let address = std::sync::Arc::new(std::sync::OnceLock::<String>::new());
+        let base_url = address.clone();
+        let server = MockServer::start(move |target| match target {
+            "/points/39.7456,-97.0892" => {
+                MockResponse::ok(POINTS.replace(API_URL, base_url.get().unwrap()))
+            }
+            "/gridpoints/TOP/32,81/forecast?units=si" => MockResponse::ok(FORECAST),
+            "/gridpoints/TOP/32,81/forecast/hourly?units=si" => {
+                MockResponse::ok(FORECAST_HOURLY).header("Expires", EXPIRES)
+            }
+            _ => MockResponse::status(404),
+        });
+        address.set(server.url("")).unwrap();
+        server
+    }
+
+    fn central(day: u32, hour: u32) -> DateTime<FixedOffset> {
+        FixedOffset::west_opt(5 * 3600)
+            .unwrap()
+            .with_ymd_and_hms(2024, 6, day, hour, 0, 0)
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn fetch_forecast() {
+        let server = start_server();
+        let provider = Nws {
+            base_url: server.url(""),
+            gridpoint: None,
+        };
+
+        let (forecast_state, _) = provider.get_weather_data(COORDS, None).await.unwrap();
+
+        assert_eq!(
+            forecast_state.expires,
+            DateTime::parse_from_rfc2822(EXPIRES).ok()
+        );
+        let gridpoint = forecast_state.gridpoint.unwrap();
+        assert_eq!(
+            gridpoint.forecast_hourly,
+            server.url("/gridpoints/TOP/32,81/forecast/hourly")
+        );
+
+        let weather_data = forecast_state.weather_data.unwrap();
+        assert_eq!(weather_data.coordinates, COORDS);
+        assert_eq!(
+            weather_data.updated_at,
+            Utc.with_ymd_and_hms(2024, 6, 3, 18, 40, 32).unwrap()
+        );
+
+        let hourly = &weather_data.hourly;
+        assert_eq!(hourly.len(), 7);
+        assert_eq!(hourly[0].interval.start, central(3, 14));
+        assert_eq!(hourly[0].interval.end, central(3, 15));
+        assert_eq!(
+            hourly[0].conditions.air_temperature,
+            Some(Temperature(27.0))
+        );
+        assert_eq!(
+            hourly[0].conditions.relative_humidity,
+            Some(Percentage(55.0))
+        );
+        assert_eq!(
+            hourly[0].conditions.wind_from_direction,
+            Some(Bearing(180.0))
+        );
+        assert_eq!(
+            hourly[0].conditions.wind_speed,
+            Some(Speed::from_kilometres_per_hour(15.0))
+        );
+        assert_eq!(
+            hourly[0].symbol,
+            Some(WeatherSymbol::PartlyCloudy(Variant::Day))
+        );
+        assert_eq!(
+            hourly[1].probability_of_precipitation,
+            Some(Percentage(20.0))
+        );
+        assert_eq!(hourly[4].probability_of_precipitation, None);
+        assert_eq!(hourly[6].conditions.wind_from_direction, None);
+        assert_eq!(
+            hourly[6].symbol,
+            Some(WeatherSymbol::ClearSky(Variant::Night))
+        );
+
+        let daily = &weather_data.daily;
+        assert_eq!(daily.len(), 3);
+        assert_eq!(daily[0].date.to_string(), "2024-06-03");
+        assert_eq!(daily[0].interval.start, central(3, 14));
+        assert_eq!(daily[0].interval.end, central(4, 6));
+        assert_eq!(daily[0].air_temperature_max, Some(Temperature(28.0)));
+        assert_eq!(daily[0].air_temperature_min, Some(Temperature(16.0)));
+        assert_eq!(daily[0].symbol, Some(WeatherSymbol::RainAndThunder));
+        assert_eq!(
+            daily[0].probability_of_precipitation,
+            Some(Percentage(40.0))
+        );
+        assert_eq!(daily[1].symbol, Some(WeatherSymbol::Fair(Variant::Day)));
+        assert_eq!(daily[1].probability_of_precipitation, Some(Percentage(5.0)));
+        assert_eq!(daily[2].air_temperature_min, None);
+        assert_eq!(daily[2].symbol, Some(WeatherSymbol::Rain));
+
+        let targets: Vec<String> = server
+            .requests()
+            .into_iter()
+            .map(|request| request.target)
+            .collect();
+        assert_eq!(
+            targets,
+            [
+                "/points/39.7456,-97.0892",
+                "/gridpoints/TOP/32,81/forecast/hourly?units=si",
+                "/gridpoints/TOP/32,81/forecast?units=si",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn reuse_gridpoint() {
+        let server = start_server();
+        let gridpoint = Gridpoint {
+            latitude: COORDS.0,
+            longitude: COORDS.1,
+            forecast: server.url("/gridpoints/TOP/32,81/forecast"),
+            forecast_hourly: server.url("/gridpoints/TOP/32,81/forecast/hourly"),
+        };
+        let provider = Nws {
+            base_url: server.url(""),
+            gridpoint: Some(gridpoint.clone()),
+        };
+
+        let (forecast_state, _) = provider.get_weather_data(COORDS, None).await.unwrap();
+
+        assert_eq!(forecast_state.gridpoint, Some(gridpoint));
+        assert!(server
+            .requests()
+            .iter()
+            .all(|request| !request.target.starts_with("/points/")));
+    }
+
+    #[tokio::test]
+    async fn resolve_gridpoint_of_other_location() {
+        let server = start_server();
+        let provider = Nws {
+            base_url: server.url(""),
+            gridpoint: Some(Gridpoint {
+                latitude: 40.0,
+                longitude: -105.0,
+                forecast: server.url("/gridpoints/BOU/1,1/forecast"),
+                forecast_hourly: server.url("/gridpoints/BOU/1,1/forecast/hourly"),
+            }),
+        };
+
+        let (forecast_state, _) = provider.get_weather_data(COORDS, None).await.unwrap();
+
+        assert_eq!(
+            forecast_state.gridpoint.map(|gridpoint| gridpoint.forecast),
+            Some(server.url("/gridpoints/TOP/32,81/forecast"))
+        );
+        assert_eq!(server.requests()[0].target, "/points/39.7456,-97.0892");
+    }
+
+    #[test]
+    fn wind_speeds() {
+        assert_eq!(wind_speed("10 mph"), Some(Speed::from_miles_per_hour(10.0)));
+        assert_eq!(
+            wind_speed("5 to 15 km/h"),
+            Some(Speed::from_kilometres_per_hour(15.0))
+        );
+        assert_eq!(wind_speed("4 m/s"), Some(Speed(4.0)));
+        assert_eq!(wind_speed("0 km/h"), Some(Speed(0.0)));
+        assert_eq!(wind_speed("calm"), None);
+        assert_eq!(wind_speed(""), None);
+    }
+
+    #[test]
+    fn compass_points() {
+        assert_eq!(compass_to_degrees("N"), Some(0.0));
+        assert_eq!(compass_to_degrees("ENE"), Some(67.5));
+        assert_eq!(compass_to_degrees("S"), Some(180.0));
+        assert_eq!(compass_to_degrees("NNW"), Some(337.5));
+        assert_eq!(compass_to_degrees(""), None);
+        assert_eq!(compass_to_degrees("north"), None);
+    }
+
+    fn period(icon: Option<&str>, is_daytime: bool) -> Period {
+        Period {
+            start_time: central(3, 14),
+            end_time: central(3, 15),
+            is_daytime,
+            temperature: None,
+            temperature_unit: None,
+            probability_of_precipitation: None,
+            relative_humidity: None,
+            wind_speed: None,
+            wind_direction: None,
+            icon: icon.map(|icon| format!("{API_URL}/icons/land/{icon}")),
+        }
+    }
+
+    #[test]
+    fn period_symbols() {
+        let symbol = |icon, is_daytime| period(Some(icon), is_daytime).symbol();
+
+        assert_eq!(
+            symbol("day/skc?size=medium", true),
+            Some(WeatherSymbol::ClearSky(Variant::Day))
+        );
+        assert_eq!(
+            symbol("night/few?size=medium", false),
+            Some(WeatherSymbol::Fair(Variant::Night))
+        );
+        assert_eq!(
+            symbol("night/rain_showers,30?size=small", false),
+            Some(WeatherSymbol::RainShowers(Variant::Night))
+        );
+        // Periods changing conditions are shown as they end
+        assert_eq!(
+            symbol("day/tsra_hi,20/sct?size=medium", true),
+            Some(WeatherSymbol::PartlyCloudy(Variant::Day))
+        );
+        assert_eq!(symbol("day/ovc", true), Some(WeatherSymbol::Cloudy));
+        assert_eq!(symbol("day/fzra,50", true), Some(WeatherSymbol::Sleet));
+        assert_eq!(symbol("day/volcano", true), Some(WeatherSymbol::Unknown));
+        assert_eq!(period(None, true).symbol(), None);
+    }
+
+    #[test]
+    fn period_temperature() {
+        let mut period = period(None, true);
+        period.temperature = Some(50.0);
+        period.temperature_unit = Some("F".to_string());
+        assert_eq!(period.temperature(), Some(Temperature(10.0)));
+
+        period.temperature_unit = Some("C".to_string());
+        assert_eq!(period.temperature(), Some(Temperature(50.0)));
+    }
+}
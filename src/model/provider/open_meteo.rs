@@ -0,0 +1,497 @@
+use std::future::Future;
+
+use chrono::{Duration, FixedOffset, Local, TimeZone, Utc};
+use chrono_tz::Tz;
+use serde::Deserialize;
+
+use crate::app::config::ForecastState;
+use crate::fl;
+use crate::model::error::ForecastError;
+use crate::model::http;
+use crate::model::quantity::{
+    Bearing, Distance, Percentage, Precipitation, Pressure, Speed, Temperature,
+};
+use crate::model::symbol::{Variant, WeatherSymbol};
+use crate::model::weather::{
+    start_of_day, Conditions, DailyForecast, HourlyForecast, Interval, PrecipitationWindow,
+    WeatherData, WeatherRequestStatus,
+};
+
+use super::{Field, WeatherProvider};
+
+const FORECAST_URL: &str = "https://api.open-meteo.com/v1/forecast";
+
+const HOURLY_VARIABLES: &str = "temperature_2m,apparent_temperature,relative_humidity_2m,\
+dew_point_2m,precipitation_probability,precipitation,weather_code,pressure_msl,cloud_cover,\
+visibility,wind_speed_10m,wind_gusts_10m,wind_direction_10m,uv_index,is_day";
+
+const DAILY_VARIABLES: &str = "weather_code,temperature_2m_max,temperature_2m_min,\
+precipitation_probability_max,precipitation_sum,wind_speed_10m_max,wind_gusts_10m_max";
+
+/// Forecast API from Open-Meteo.
+///
+/// `base_url` defaults to the public endpoint and can be pointed at a local
+/// server replaying recorded responses.
+#[derive(Clone, Debug)]
+pub struct OpenMeteo {
+    pub base_url: String,
+}
+
+impl Default for OpenMeteo {
+    fn default() -> Self {
+        Self {
+            base_url: FORECAST_URL.to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct Response {
+    latitude: f64,
+    longitude: f64,
+    utc_offset_seconds: i32,
+    /// IANA name of the timezone days are aggregated in.
+    #[serde(default)]
+    timezone: String,
+    hourly: Hourly,
+    daily: Daily,
+}
+
+#[derive(Debug, Deserialize)]
+struct Hourly {
+    time: Vec<i64>,
+    temperature_2m: Vec<Option<f64>>,
+    apparent_temperature: Vec<Option<f64>>,
+    relative_humidity_2m: Vec<Option<f64>>,
+    dew_point_2m: Vec<Option<f64>>,
+    precipitation_probability: Vec<Option<f64>>,
+    precipitation: Vec<Option<f64>>,
+    weather_code: Vec<Option<u8>>,
+    pressure_msl: Vec<Option<f64>>,
+    cloud_cover: Vec<Option<f64>>,
+    visibility: Vec<Option<f64>>,
+    wind_speed_10m: Vec<Option<f64>>,
+    wind_gusts_10m: Vec<Option<f64>>,
+    wind_direction_10m: Vec<Option<f64>>,
+    uv_index: Vec<Option<f64>>,
+    is_day: Vec<Option<u8>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Daily {
+    time: Vec<i64>,
+    weather_code: Vec<Option<u8>>,
+    temperature_2m_max: Vec<Option<f64>>,
+    temperature_2m_min: Vec<Option<f64>>,
+    precipitation_probability_max: Vec<Option<f64>>,
+    precipitation_sum: Vec<Option<f64>>,
+    wind_speed_10m_max: Vec<Option<f64>>,
+    wind_gusts_10m_max: Vec<Option<f64>>,
+}
+
+impl WeatherProvider for OpenMeteo {
+    fn attribution(&self) -> String {
+        fl!("attribution-open-meteo")
+    }
+
+    fn supported_fields(&self) -> &'static [Field] {
+        &[
+            Field::AirPressure,
+            Field::AirTemperature,
+            Field::AirTemperatureMax,
+            Field::AirTemperatureMin,
+            Field::ApparentTemperature,
+            Field::CloudAreaFraction,
+            Field::DewPointTemperature,
+            Field::PrecipitationAmount,
+            Field::ProbabilityOfPrecipitation,
+            Field::RelativeHumidity,
+            Field::UltravioletIndex,
+            Field::Visibility,
+            Field::WindFromDirection,
+            Field::WindSpeed,
+            Field::WindSpeedOfGust,
+            Field::SymbolCode,
+        ]
+    }
+
+    fn get_weather_data(
+        &self,
+        coords: (f64, f64),
+        _last_request: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> impl Future<Output = Result<(ForecastState, WeatherRequestStatus), ForecastError>>
+           + Send
+           + 'static {
+        let base_url = self.base_url.clone();
+        async move {
+            let query_params = [
+                ("latitude", coords.0.to_string()),
+                ("longitude", coords.1.to_string()),
+                ("hourly", HOURLY_VARIABLES.to_string()),
+                ("daily", DAILY_VARIABLES.to_string()),
+                ("timeformat", "unixtime".to_string()),
+                // Daily values are aggregated over the days of the location
+                ("timezone", "auto".to_string()),
+                ("wind_speed_unit", "ms".to_string()),
+                ("forecast_days", "10".to_string()),
+            ];
+
+            let res = http::send(http::client().get(base_url).query(&query_params)).await?;
+
+            let response: Response = ForecastError::json(res).await?;
+            let last_request: chrono::DateTime<chrono::FixedOffset> = Utc::now().into();
+
+            // Open-Meteo updates its models hourly and sends no caching headers
+            let expires = last_request + Duration::hours(1);
+
+            let forecast_state = ForecastState {
+                last_request: Some(last_request),
+                expires: Some(expires),
+                weather_data: Some(response.into_weather_data()),
+                gridpoint: None,
+                refresh_failed: false,
+            };
+            Ok((forecast_state, WeatherRequestStatus::Other))
+        }
+    }
+}
+
+impl Response {
+    fn into_weather_data(self) -> WeatherData {
+        let value = |values: &Vec<Option<f64>>, index: usize| values.get(index).copied().flatten();
+
+        let hourly = &self.hourly;
+        let hourly_forecast: Vec<HourlyForecast> = hourly
+            .time
+            .iter()
+            .enumerate()
+            .filter_map(|(index, time)| {
+                let start = Utc.timestamp_opt(*time, 0).single()?.with_timezone(&Local);
+                let is_day = hourly.is_day.get(index).copied().flatten() != Some(0);
+                let probability_of_precipitation =
+                    value(&hourly.precipitation_probability, index).map(Percentage);
+                let precipitation_amount = value(&hourly.precipitation, index).map(Precipitation);
+
+                Some(HourlyForecast {
+                    interval: Interval {
+                        start,
+                        end: start + Duration::hours(1),
+                    },
+                    conditions: Conditions {
+                        air_pressure_at_sea_level: value(&hourly.pressure_msl, index).map(Pressure),
+                        air_temperature: value(&hourly.temperature_2m, index).map(Temperature),
+                        apparent_temperature: value(&hourly.apparent_temperature, index)
+                            .map(Temperature),
+                        cloud_area_fraction: value(&hourly.cloud_cover, index).map(Percentage),
+                        cloud_area_fraction_low: None,
+                        cloud_area_fraction_medium: None,
+                        cloud_area_fraction_high: None,
+                        dew_point_temperature: value(&hourly.dew_point_2m, index).map(Temperature),
+                        fog_area_fraction: None,
+                        relative_humidity: value(&hourly.relative_humidity_2m, index)
+                            .map(Percentage),
+                        ultraviolet_index_clear_sky: value(&hourly.uv_index, index),
+                        visibility: value(&hourly.visibility, index).map(Distance),
+                        wind_from_direction: value(&hourly.wind_direction_10m, index).map(Bearing),
+                        wind_speed: value(&hourly.wind_speed_10m, index).map(Speed),
+                        wind_speed_of_gust: value(&hourly.wind_gusts_10m, index).map(Speed),
+                    },
+                    symbol: hourly
+                        .weather_code
+                        .get(index)
+                        .copied()
+                        .flatten()
+                        .map(|code| symbol(code, is_day)),
+                    probability_of_precipitation,
+                    precipitation_amount,
+                    air_temperature_max: None,
+                    air_temperature_min: None,
+                    precipitation_windows: vec![PrecipitationWindow {
+                        hours: 1,
+                        amount: precipitation_amount,
+                        amount_min: None,
+                        amount_max: None,
+                        probability: probability_of_precipitation,
+                    }],
+                })
+            })
+            .collect();
+
+        // The offset is the current one, which differs from that of days
+        // after a DST change
+        let timezone: Option<Tz> = self.timezone.parse().ok();
+        let offset = FixedOffset::east_opt(self.utc_offset_seconds)
+            .unwrap_or_else(|| FixedOffset::east_opt(0).unwrap());
+        let daily = &self.daily;
+        let daily_forecast = daily
+            .time
+            .iter()
+            .enumerate()
+            .filter_map(|(index, time)| {
+                let start = Utc.timestamp_opt(*time, 0).single()?;
+                let date = match timezone {
+                    Some(timezone) => start.with_timezone(&timezone).date_naive(),
+                    None => start.with_timezone(&offset).date_naive(),
+                };
+                // A day ends where the next starts, which is not 24 hours
+                // later on DST changes
+                let end = match (daily.time.get(index + 1), timezone) {
+                    (Some(next), _) => Utc.timestamp_opt(*next, 0).single()?,
+                    (None, Some(timezone)) => {
+                        start_of_day(date.succ_opt()?, &timezone).with_timezone(&Utc)
+                    }
+                    (None, None) => start + Duration::days(1),
+                };
+                Some(DailyForecast {
+                    date,
+                    interval: Interval {
+                        start: start.with_timezone(&Local),
+                        end: end.with_timezone(&Local),
+                    },
+                    symbol: daily
+                        .weather_code
+                        .get(index)
+                        .copied()
+                        .flatten()
+                        .map(|code| symbol(code, true)),
+                    air_temperature_max: value(&daily.temperature_2m_max, index).map(Temperature),
+                    air_temperature_min: value(&daily.temperature_2m_min, index).map(Temperature),
+                    probability_of_precipitation: value(
+                        &daily.precipitation_probability_max,
+                        index,
+                    )
+                    .map(Percentage),
+                    precipitation_amount: value(&daily.precipitation_sum, index).map(Precipitation),
+                    precipitation_amount_min: None,
+                    precipitation_amount_max: None,
+                    wind_speed_max: value(&daily.wind_speed_10m_max, index).map(Speed),
+                    wind_speed_of_gust_max: value(&daily.wind_gusts_10m_max, index).map(Speed),
+                })
+            })
+            .collect();
+
+        let now = Local::now();
+        WeatherData {
+            updated_at: now,
+            coordinates: (self.latitude, self.longitude),
+            current: hourly_forecast
+                .iter()
+                .find(|forecast| forecast.interval.contains(now))
+                .cloned(),
+            hourly: hourly_forecast,
+            daily: daily_forecast,
+        }
+    }
+}
+
+/// Map a WMO weather interpretation code to the matching met.no symbol.
+fn symbol(code: u8, is_day: bool) -> WeatherSymbol {
+    let variant = if is_day { Variant::Day } else { Variant::Night };
+    match code {
+        0 => WeatherSymbol::ClearSky(variant),
+        1 => WeatherSymbol::Fair(variant),
+        2 => WeatherSymbol::PartlyCloudy(variant),
+        3 => WeatherSymbol::Cloudy,
+        45 | 48 => WeatherSymbol::Fog,
+        51 | 53 | 61 => WeatherSymbol::LightRain,
+        55 | 63 => WeatherSymbol::Rain,
+        65 => WeatherSymbol::HeavyRain,
+        56 | 66 => WeatherSymbol::LightSleet,
+        57 | 67 => WeatherSymbol::Sleet,
+        71 | 77 => WeatherSymbol::LightSnow,
+        73 => WeatherSymbol::Snow,
+        75 => WeatherSymbol::HeavySnow,
+        80 => WeatherSymbol::LightRainShowers(variant),
+        81 => WeatherSymbol::RainShowers(variant),
+        82 => WeatherSymbol::HeavyRainShowers(variant),
+        85 => WeatherSymbol::LightSnowShowers(variant),
+        86 => WeatherSymbol::HeavySnowShowers(variant),
+        95 => WeatherSymbol::RainAndThunder,
+        96 | 99 => WeatherSymbol::HeavyRainAndThunder,
+        _ => {
+            log::warn!("unknown WMO weather code {code}");
+            WeatherSymbol::Unknown
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::DateTime;
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+    use crate::model::mock_server::{MockResponse, MockServer};
+
+    /// Berlin from 26 to 28 October 2024, the 27th is 25 hours long.
+    const FORECAST: &str = include_str!("fixtures/open_meteo_forecast.json");
+
+    fn berlin(day: u32, hour: u32) -> DateTime<Tz> {
+        Berlin.with_ymd_and_hms(2024, 10, day, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn hourly_forecast() {
+        let response: Response = serde_json::from_str(FORECAST).unwrap();
+        let weather_data = response.into_weather_data();
+
+        assert_eq!(weather_data.coordinates, (52.52, 13.419998));
+        assert_eq!(weather_data.hourly.len(), 73);
+
+        let first = &weather_data.hourly[0];
+        assert_eq!(first.interval.start, berlin(26, 0));
+        assert_eq!(first.interval.end, berlin(26, 1));
+        assert_eq!(first.conditions.air_temperature, Some(Temperature(5.5)));
+        assert_eq!(
+            first.conditions.apparent_temperature,
+            Some(Temperature(3.2))
+        );
+        assert_eq!(
+            first.conditions.air_pressure_at_sea_level,
+            Some(Pressure(1016.2))
+        );
+        assert_eq!(first.conditions.relative_humidity, Some(Percentage(90.0)));
+        assert_eq!(
+            first.conditions.dew_point_temperature,
+            Some(Temperature(4.0))
+        );
+        assert_eq!(first.conditions.visibility, Some(Distance(11000.0)));
+        assert_eq!(first.conditions.wind_from_direction, Some(Bearing(200.0)));
+        assert_eq!(first.conditions.wind_speed, Some(Speed(2.5)));
+        assert_eq!(first.conditions.cloud_area_fraction_low, None);
+        assert_eq!(first.symbol, Some(WeatherSymbol::ClearSky(Variant::Night)));
+
+        let rain = &weather_data.hourly[32];
+        assert_eq!(rain.symbol, Some(WeatherSymbol::Rain));
+        assert_eq!(rain.precipitation_amount, Some(Precipitation(1.2)));
+        assert_eq!(rain.probability_of_precipitation, Some(Percentage(60.0)));
+        assert_eq!(
+            rain.precipitation_window().and_then(|window| window.amount),
+            Some(Precipitation(1.2))
+        );
+
+        let noon = &weather_data.hourly[12];
+        assert_eq!(noon.symbol, Some(WeatherSymbol::PartlyCloudy(Variant::Day)));
+        assert_eq!(
+            weather_data.hourly[72]
+                .conditions
+                .ultraviolet_index_clear_sky,
+            None
+        );
+        assert_eq!(weather_data.hourly[72].conditions.visibility, None);
+    }
+
+    #[test]
+    fn daily_forecast_across_dst_change() {
+        let response: Response = serde_json::from_str(FORECAST).unwrap();
+        let daily = response.into_weather_data().daily;
+
+        assert_eq!(daily.len(), 3);
+        assert_eq!(
+            daily
+                .iter()
+                .map(|day| day.date.to_string())
+                .collect::<Vec<_>>(),
+            ["2024-10-26", "2024-10-27", "2024-10-28"]
+        );
+        assert_eq!(daily[1].interval.start, berlin(27, 0));
+        assert_eq!(daily[1].interval.end, berlin(28, 0));
+        assert_eq!(daily[2].interval.end, berlin(29, 0));
+
+        assert_eq!(daily[1].symbol, Some(WeatherSymbol::Rain));
+        assert_eq!(daily[1].air_temperature_max, Some(Temperature(14.4)));
+        assert_eq!(daily[1].air_temperature_min, Some(Temperature(4.4)));
+        assert_eq!(daily[1].precipitation_amount, Some(Precipitation(2.9)));
+        assert_eq!(
+            daily[1].probability_of_precipitation,
+            Some(Percentage(60.0))
+        );
+        assert_eq!(daily[1].wind_speed_of_gust_max, Some(Speed(7.6)));
+    }
+
+    #[test]
+    fn daily_forecast_without_timezone() {
+        let mut response: Response = serde_json::from_str(FORECAST).unwrap();
+        response.timezone.clear();
+        let daily = response.into_weather_data().daily;
+
+        // Only the last day, which has no next one, falls back to 24 hours
+        assert_eq!(daily[1].interval.end, berlin(28, 0));
+        assert_eq!(daily[2].interval.end, berlin(29, 0));
+    }
+
+    #[test]
+    fn symbols() {
+        assert_eq!(symbol(0, true), WeatherSymbol::ClearSky(Variant::Day));
+        assert_eq!(symbol(1, false), WeatherSymbol::Fair(Variant::Night));
+        assert_eq!(symbol(3, false), WeatherSymbol::Cloudy);
+        assert_eq!(symbol(48, true), WeatherSymbol::Fog);
+        assert_eq!(symbol(57, true), WeatherSymbol::Sleet);
+        assert_eq!(symbol(75, true), WeatherSymbol::HeavySnow);
+        assert_eq!(
+            symbol(81, false),
+            WeatherSymbol::RainShowers(Variant::Night)
+        );
+        assert_eq!(symbol(99, true), WeatherSymbol::HeavyRainAndThunder);
+        assert_eq!(symbol(42, true), WeatherSymbol::Unknown);
+    }
+
+    #[tokio::test]
+    async fn fetch_forecast() {
+        let server = MockServer::start(|target| {
+            if target.starts_with("/v1/forecast?") {
+                MockResponse::ok(FORECAST)
+            } else {
+                MockResponse::status(404)
+            }
+        });
+        let provider = OpenMeteo {
+            base_url: server.url("/v1/forecast"),
+        };
+
+        let (forecast_state, status) = provider
+            .get_weather_data((52.52, 13.42), None)
+            .await
+            .unwrap();
+
+        assert!(matches!(status, WeatherRequestStatus::Other));
+        assert_eq!(
+            forecast_state.expires,
+            forecast_state
+                .last_request
+                .map(|last_request| last_request + Duration::hours(1))
+        );
+        let weather_data = forecast_state.weather_data.unwrap();
+        assert_eq!(weather_data.hourly.len(), 73);
+        assert_eq!(weather_data.daily.len(), 3);
+
+        let requests = server.requests();
+        assert_eq!(requests.len(), 1);
+        for param in [
+            "latitude=52.52",
+            "longitude=13.42",
+            "timezone=auto",
+            "timeformat=unixtime",
+        ] {
+            assert!(requests[0].target.contains(param), "{}", requests[0].target);
+        }
+    }
+
+    #[tokio::test]
+    async fn fetch_error_status() {
+        let server = MockServer::start(|_| MockResponse::status(400));
+        let provider = OpenMeteo {
+            base_url: server.url("/v1/forecast"),
+        };
+
+        let error = provider
+            .get_weather_data((52.52, 13.42), None)
+            .await
+            .unwrap_err();
+
+        assert!(
+            matches!(error, ForecastError::Status { status: 400, .. }),
+            "{error:?}"
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+/// Temperature in degrees Celsius.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct Temperature(pub f64);
+
+impl Temperature {
+    pub fn from_fahrenheit(value: f64) -> Self {
+        Self((value - 32_f64) * (5_f64 / 9_f64))
+    }
+
+    pub fn celsius(self) -> f64 {
+        self.0
+    }
+
+    pub fn fahrenheit(self) -> f64 {
+        (self.0 * (9_f64 / 5_f64)) + 32_f64
+    }
+}
+
+/// Air pressure in hectopascal.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct Pressure(pub f64);
+
+impl Pressure {
+    pub fn hectopascal(self) -> f64 {
+        self.0
+    }
+
+    pub fn bar(self) -> f64 {
+        self.0 * 0.001_f64
+    }
+
+    pub fn kilopascal(self) -> f64 {
+        self.0 * 0.1_f64
+    }
+
+    pub fn psi(self) -> f64 {
+        self.0 * 0.0145037738_f64
+    }
+}
+
+/// Speed in metres per second.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct Speed(pub f64);
+
+impl Speed {
+    pub fn from_miles_per_hour(value: f64) -> Self {
+        Self(value * 0.44704_f64)
+    }
+
+    pub fn from_kilometres_per_hour(value: f64) -> Self {
+        Self(value / 3.6_f64)
+    }
+
+    pub fn metres_per_second(self) -> f64 {
+        self.0
+    }
+
+    pub fn miles_per_hour(self) -> f64 {
+        self.0 / 0.44704_f64
+    }
+
+    pub fn kilometres_per_hour(self) -> f64 {
+        self.0 * 3.6_f64
+    }
+}
+
+/// Precipitation amount in millimetres.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct Precipitation(pub f64);
+
+impl Precipitation {
+    pub fn millimetres(self) -> f64 {
+        self.0
+    }
+
+    pub fn inches(self) -> f64 {
+        self.0 / 25.4
+    }
+}
+
+/// Distance in metres.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct Distance(pub f64);
+
+impl Distance {
+    pub fn kilometres(self) -> f64 {
+        self.0 / 1000_f64
+    }
+
+    pub fn miles(self) -> f64 {
+        self.0 / 1609.344_f64
+    }
+}
+
+/// Fraction in percent, from 0 to 100.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct Percentage(pub f64);
+
+/// Direction in degrees clockwise from north.
+#[derive(Clone, Copy, Debug, Default, Deserialize, Serialize, PartialEq, PartialOrd)]
+pub struct Bearing(pub f64);
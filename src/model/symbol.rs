@@ -0,0 +1,494 @@
+use std::fmt;
+use std::str::FromStr;
+
+use cosmic::widget;
+use serde::{Deserialize, Serialize};
+
+use crate::app::icon_cache::WEATHER_ICONS;
+use crate::fl;
+
+/// Time of day a symbol is drawn for.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum Variant {
+    Day,
+    Night,
+    PolarTwilight,
+}
+
+impl Variant {
+    /// Name of the variant, e.g. `night`.
+    pub fn name(self) -> &'static str {
+        &self.suffix()[1..]
+    }
+
+    fn suffix(self) -> &'static str {
+        match self {
+            Self::Day => "_day",
+            Self::Night => "_night",
+            Self::PolarTwilight => "_polartwilight",
+        }
+    }
+}
+
+/// Weather symbol as defined by met.no.
+///
+/// Variants are declared from least to most severe, so the derived ordering
+/// can be used to pick the dominant symbol of a period.
+#[derive(Clone, Copy, Debug, Deserialize, Eq, Hash, Ord, PartialEq, PartialOrd, Serialize)]
+#[serde(from = "String", into = "String")]
+pub enum WeatherSymbol {
+    /// A code this version does not know about.
+    Unknown,
+    ClearSky(Variant),
+    Fair(Variant),
+    PartlyCloudy(Variant),
+    Cloudy,
+    Fog,
+    LightRainShowers(Variant),
+    LightRain,
+    LightSleetShowers(Variant),
+    LightSleet,
+    LightSnowShowers(Variant),
+    LightSnow,
+    RainShowers(Variant),
+    Rain,
+    SleetShowers(Variant),
+    Sleet,
+    SnowShowers(Variant),
+    Snow,
+    HeavyRainShowers(Variant),
+    HeavyRain,
+    HeavySleetShowers(Variant),
+    HeavySleet,
+    HeavySnowShowers(Variant),
+    HeavySnow,
+    LightRainShowersAndThunder(Variant),
+    LightRainAndThunder,
+    LightSleetShowersAndThunder(Variant),
+    LightSleetAndThunder,
+    LightSnowShowersAndThunder(Variant),
+    LightSnowAndThunder,
+    RainShowersAndThunder(Variant),
+    RainAndThunder,
+    SleetShowersAndThunder(Variant),
+    SleetAndThunder,
+    SnowShowersAndThunder(Variant),
+    SnowAndThunder,
+    HeavyRainShowersAndThunder(Variant),
+    HeavyRainAndThunder,
+    HeavySleetShowersAndThunder(Variant),
+    HeavySleetAndThunder,
+    HeavySnowShowersAndThunder(Variant),
+    HeavySnowAndThunder,
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct ParseSymbolError(String);
+
+impl fmt::Display for ParseSymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "unknown weather symbol code {:?}", self.0)
+    }
+}
+
+impl std::error::Error for ParseSymbolError {}
+
+impl FromStr for WeatherSymbol {
+    type Err = ParseSymbolError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        let error = || ParseSymbolError(code.to_string());
+
+        let (name, variant) = match code.rsplit_once('_') {
+            Some((name, "day")) => (name, Some(Variant::Day)),
+            Some((name, "night")) => (name, Some(Variant::Night)),
+            Some((name, "polartwilight")) => (name, Some(Variant::PolarTwilight)),
+            Some(_) => return Err(error()),
+            None => (code, None),
+        };
+
+        let symbol = match (name, variant) {
+            ("clearsky", Some(variant)) => Self::ClearSky(variant),
+            ("fair", Some(variant)) => Self::Fair(variant),
+            ("partlycloudy", Some(variant)) => Self::PartlyCloudy(variant),
+            ("cloudy", None) => Self::Cloudy,
+            ("fog", None) => Self::Fog,
+            ("lightrainshowers", Some(variant)) => Self::LightRainShowers(variant),
+            ("lightrain", None) => Self::LightRain,
+            ("lightsleetshowers", Some(variant)) => Self::LightSleetShowers(variant),
+            ("lightsleet", None) => Self::LightSleet,
+            ("lightsnowshowers", Some(variant)) => Self::LightSnowShowers(variant),
+            ("lightsnow", None) => Self::LightSnow,
+            ("rainshowers", Some(variant)) => Self::RainShowers(variant),
+            ("rain", None) => Self::Rain,
+            ("sleetshowers", Some(variant)) => Self::SleetShowers(variant),
+            ("sleet", None) => Self::Sleet,
+            ("snowshowers", Some(variant)) => Self::SnowShowers(variant),
+            ("snow", None) => Self::Snow,
+            ("heavyrainshowers", Some(variant)) => Self::HeavyRainShowers(variant),
+            ("heavyrain", None) => Self::HeavyRain,
+            ("heavysleetshowers", Some(variant)) => Self::HeavySleetShowers(variant),
+            ("heavysleet", None) => Self::HeavySleet,
+            ("heavysnowshowers", Some(variant)) => Self::HeavySnowShowers(variant),
+            ("heavysnow", None) => Self::HeavySnow,
+            ("lightrainshowersandthunder", Some(variant)) => {
+                Self::LightRainShowersAndThunder(variant)
+            }
+            ("lightrainandthunder", None) => Self::LightRainAndThunder,
+            ("lightssleetshowersandthunder" | "lightsleetshowersandthunder", Some(variant)) => {
+                Self::LightSleetShowersAndThunder(variant)
+            }
+            ("lightsleetandthunder", None) => Self::LightSleetAndThunder,
+            ("lightssnowshowersandthunder" | "lightsnowshowersandthunder", Some(variant)) => {
+                Self::LightSnowShowersAndThunder(variant)
+            }
+            ("lightsnowandthunder", None) => Self::LightSnowAndThunder,
+            ("rainshowersandthunder", Some(variant)) => Self::RainShowersAndThunder(variant),
+            ("rainandthunder", None) => Self::RainAndThunder,
+            ("sleetshowersandthunder", Some(variant)) => Self::SleetShowersAndThunder(variant),
+            ("sleetandthunder", None) => Self::SleetAndThunder,
+            ("snowshowersandthunder", Some(variant)) => Self::SnowShowersAndThunder(variant),
+            ("snowandthunder", None) => Self::SnowAndThunder,
+            ("heavyrainshowersandthunder", Some(variant)) => {
+                Self::HeavyRainShowersAndThunder(variant)
+            }
+            ("heavyrainandthunder", None) => Self::HeavyRainAndThunder,
+            ("heavysleetshowersandthunder", Some(variant)) => {
+                Self::HeavySleetShowersAndThunder(variant)
+            }
+            ("heavysleetandthunder", None) => Self::HeavySleetAndThunder,
+            ("heavysnowshowersandthunder", Some(variant)) => {
+                Self::HeavySnowShowersAndThunder(variant)
+            }
+            ("heavysnowandthunder", None) => Self::HeavySnowAndThunder,
+            _ => return Err(error()),
+        };
+        Ok(symbol)
+    }
+}
+
+impl From<String> for WeatherSymbol {
+    fn from(code: String) -> Self {
+        Self::parse_or_unknown(&code)
+    }
+}
+
+impl From<WeatherSymbol> for String {
+    fn from(symbol: WeatherSymbol) -> Self {
+        symbol.code()
+    }
+}
+
+impl fmt::Display for WeatherSymbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.code())
+    }
+}
+
+impl WeatherSymbol {
+    /// Parse a met.no symbol code, logging codes that are not known.
+    pub fn parse_or_unknown(code: &str) -> Self {
+        code.parse().unwrap_or_else(|err| {
+            log::warn!("{err}");
+            Self::Unknown
+        })
+    }
+
+    /// Code without the variant suffix, spelled like the bundled icons.
+    fn name(&self) -> (&'static str, Option<Variant>) {
+        match *self {
+            Self::Unknown => ("unknown", None),
+            Self::ClearSky(variant) => ("clearsky", Some(variant)),
+            Self::Fair(variant) => ("fair", Some(variant)),
+            Self::PartlyCloudy(variant) => ("partlycloudy", Some(variant)),
+            Self::Cloudy => ("cloudy", None),
+            Self::Fog => ("fog", None),
+            Self::LightRainShowers(variant) => ("lightrainshowers", Some(variant)),
+            Self::LightRain => ("lightrain", None),
+            Self::LightSleetShowers(variant) => ("lightsleetshowers", Some(variant)),
+            Self::LightSleet => ("lightsleet", None),
+            Self::LightSnowShowers(variant) => ("lightsnowshowers", Some(variant)),
+            Self::LightSnow => ("lightsnow", None),
+            Self::RainShowers(variant) => ("rainshowers", Some(variant)),
+            Self::Rain => ("rain", None),
+            Self::SleetShowers(variant) => ("sleetshowers", Some(variant)),
+            Self::Sleet => ("sleet", None),
+            Self::SnowShowers(variant) => ("snowshowers", Some(variant)),
+            Self::Snow => ("snow", None),
+            Self::HeavyRainShowers(variant) => ("heavyrainshowers", Some(variant)),
+            Self::HeavyRain => ("heavyrain", None),
+            Self::HeavySleetShowers(variant) => ("heavysleetshowers", Some(variant)),
+            Self::HeavySleet => ("heavysleet", None),
+            Self::HeavySnowShowers(variant) => ("heavysnowshowers", Some(variant)),
+            Self::HeavySnow => ("heavysnow", None),
+            Self::LightRainShowersAndThunder(variant) => {
+                ("lightrainshowersandthunder", Some(variant))
+            }
+            Self::LightRainAndThunder => ("lightrainandthunder", None),
+            Self::LightSleetShowersAndThunder(variant) => {
+                ("lightssleetshowersandthunder", Some(variant))
+            }
+            Self::LightSleetAndThunder => ("lightsleetandthunder", None),
+            Self::LightSnowShowersAndThunder(variant) => {
+                ("lightssnowshowersandthunder", Some(variant))
+            }
+            Self::LightSnowAndThunder => ("lightsnowandthunder", None),
+            Self::RainShowersAndThunder(variant) => ("rainshowersandthunder", Some(variant)),
+            Self::RainAndThunder => ("rainandthunder", None),
+            Self::SleetShowersAndThunder(variant) => ("sleetshowersandthunder", Some(variant)),
+            Self::SleetAndThunder => ("sleetandthunder", None),
+            Self::SnowShowersAndThunder(variant) => ("snowshowersandthunder", Some(variant)),
+            Self::SnowAndThunder => ("snowandthunder", None),
+            Self::HeavyRainShowersAndThunder(variant) => {
+                ("heavyrainshowersandthunder", Some(variant))
+            }
+            Self::HeavyRainAndThunder => ("heavyrainandthunder", None),
+            Self::HeavySleetShowersAndThunder(variant) => {
+                ("heavysleetshowersandthunder", Some(variant))
+            }
+            Self::HeavySleetAndThunder => ("heavysleetandthunder", None),
+            Self::HeavySnowShowersAndThunder(variant) => {
+                ("heavysnowshowersandthunder", Some(variant))
+            }
+            Self::HeavySnowAndThunder => ("heavysnowandthunder", None),
+        }
+    }
+
+    /// The met.no symbol code, e.g. `partlycloudy_night`.
+    pub fn code(&self) -> String {
+        let (name, variant) = self.name();
+        match variant {
+            Some(variant) => format!("{name}{}", variant.suffix()),
+            None => name.to_string(),
+        }
+    }
+
+    pub fn variant(&self) -> Option<Variant> {
+        self.name().1
+    }
+
+    /// The same symbol drawn for `variant`, symbols without variants are unchanged.
+    pub fn with_variant(&self, variant: Variant) -> Self {
+        match self.name() {
+            (name, Some(_)) => format!("{name}{}", variant.suffix())
+                .parse()
+                .unwrap_or(*self),
+            (_, None) => *self,
+        }
+    }
+
+    pub fn description(&self) -> String {
+        match self {
+            Self::Unknown => fl!("symbol-unknown"),
+            Self::ClearSky(_) => fl!("symbol-clearsky"),
+            Self::Fair(_) => fl!("symbol-fair"),
+            Self::PartlyCloudy(_) => fl!("symbol-partlycloudy"),
+            Self::Cloudy => fl!("symbol-cloudy"),
+            Self::Fog => fl!("symbol-fog"),
+            Self::LightRainShowers(_) => fl!("symbol-lightrainshowers"),
+            Self::LightRain => fl!("symbol-lightrain"),
+            Self::LightSleetShowers(_) => fl!("symbol-lightsleetshowers"),
+            Self::LightSleet => fl!("symbol-lightsleet"),
+            Self::LightSnowShowers(_) => fl!("symbol-lightsnowshowers"),
+            Self::LightSnow => fl!("symbol-lightsnow"),
+            Self::RainShowers(_) => fl!("symbol-rainshowers"),
+            Self::Rain => fl!("symbol-rain"),
+            Self::SleetShowers(_) => fl!("symbol-sleetshowers"),
+            Self::Sleet => fl!("symbol-sleet"),
+            Self::SnowShowers(_) => fl!("symbol-snowshowers"),
+            Self::Snow => fl!("symbol-snow"),
+            Self::HeavyRainShowers(_) => fl!("symbol-heavyrainshowers"),
+            Self::HeavyRain => fl!("symbol-heavyrain"),
+            Self::HeavySleetShowers(_) => fl!("symbol-heavysleetshowers"),
+            Self::HeavySleet => fl!("symbol-heavysleet"),
+            Self::HeavySnowShowers(_) => fl!("symbol-heavysnowshowers"),
+            Self::HeavySnow => fl!("symbol-heavysnow"),
+            Self::LightRainShowersAndThunder(_) => fl!("symbol-lightrainshowersandthunder"),
+            Self::LightRainAndThunder => fl!("symbol-lightrainandthunder"),
+            Self::LightSleetShowersAndThunder(_) => fl!("symbol-lightsleetshowersandthunder"),
+            Self::LightSleetAndThunder => fl!("symbol-lightsleetandthunder"),
+            Self::LightSnowShowersAndThunder(_) => fl!("symbol-lightsnowshowersandthunder"),
+            Self::LightSnowAndThunder => fl!("symbol-lightsnowandthunder"),
+            Self::RainShowersAndThunder(_) => fl!("symbol-rainshowersandthunder"),
+            Self::RainAndThunder => fl!("symbol-rainandthunder"),
+            Self::SleetShowersAndThunder(_) => fl!("symbol-sleetshowersandthunder"),
+            Self::SleetAndThunder => fl!("symbol-sleetandthunder"),
+            Self::SnowShowersAndThunder(_) => fl!("symbol-snowshowersandthunder"),
+            Self::SnowAndThunder => fl!("symbol-snowandthunder"),
+            Self::HeavyRainShowersAndThunder(_) => fl!("symbol-heavyrainshowersandthunder"),
+            Self::HeavyRainAndThunder => fl!("symbol-heavyrainandthunder"),
+            Self::HeavySleetShowersAndThunder(_) => fl!("symbol-heavysleetshowersandthunder"),
+            Self::HeavySleetAndThunder => fl!("symbol-heavysleetandthunder"),
+            Self::HeavySnowShowersAndThunder(_) => fl!("symbol-heavysnowshowersandthunder"),
+            Self::HeavySnowAndThunder => fl!("symbol-heavysnowandthunder"),
+        }
+    }
+
+    /// Coarse category of the symbol, e.g. `rain` for all rain and rain shower codes.
+    ///
+    /// Used as CSS class in status bar output.
+    pub fn category(&self) -> &'static str {
+        let (name, _) = self.name();
+        if name.contains("thunder") {
+            "thunder"
+        } else if name.contains("snow") {
+            "snow"
+        } else if name.contains("sleet") {
+            "sleet"
+        } else if name.contains("rain") {
+            "rain"
+        } else {
+            match self {
+                Self::ClearSky(_) => "clear",
+                Self::Fair(_) | Self::PartlyCloudy(_) => "partly-cloudy",
+                Self::Cloudy => "cloudy",
+                Self::Fog => "fog",
+                _ => "unknown",
+            }
+        }
+    }
+
+    /// Bundled icon of the symbol, or a generic icon for unknown codes.
+    pub fn icon_handle(&self) -> widget::icon::Handle {
+        match WEATHER_ICONS.get_file(format!("{}.svg", self.code())) {
+            Some(file) => widget::icon::from_svg_bytes(file.contents()),
+            None => widget::icon::from_name("weather-overcast-symbolic").handle(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every symbol except [`WeatherSymbol::Unknown`], in each variant.
+    fn all_symbols() -> Vec<WeatherSymbol> {
+        use WeatherSymbol::*;
+
+        let with_variant: [fn(Variant) -> WeatherSymbol; 21] = [
+            ClearSky,
+            Fair,
+            PartlyCloudy,
+            LightRainShowers,
+            LightSleetShowers,
+            LightSnowShowers,
+            RainShowers,
+            SleetShowers,
+            SnowShowers,
+            HeavyRainShowers,
+            HeavySleetShowers,
+            HeavySnowShowers,
+            LightRainShowersAndThunder,
+            LightSleetShowersAndThunder,
+            LightSnowShowersAndThunder,
+            RainShowersAndThunder,
+            SleetShowersAndThunder,
+            SnowShowersAndThunder,
+            HeavyRainShowersAndThunder,
+            HeavySleetShowersAndThunder,
+            HeavySnowShowersAndThunder,
+        ];
+        let without_variant = [
+            Cloudy,
+            Fog,
+            LightRain,
+            LightSleet,
+            LightSnow,
+            Rain,
+            Sleet,
+            Snow,
+            HeavyRain,
+            HeavySleet,
+            HeavySnow,
+            LightRainAndThunder,
+            LightSleetAndThunder,
+            LightSnowAndThunder,
+            RainAndThunder,
+            SleetAndThunder,
+            SnowAndThunder,
+            HeavyRainAndThunder,
+            HeavySleetAndThunder,
+            HeavySnowAndThunder,
+        ];
+
+        let symbols: Vec<WeatherSymbol> = with_variant
+            .iter()
+            .flat_map(|symbol| [Variant::Day, Variant::Night, Variant::PolarTwilight].map(symbol))
+            .chain(without_variant)
+            .collect();
+
+        // Does not compile once a variant is added, which then needs to be
+        // listed above
+        for symbol in &symbols {
+            match symbol {
+                Unknown
+                | ClearSky(_)
+                | Fair(_)
+                | PartlyCloudy(_)
+                | Cloudy
+                | Fog
+                | LightRainShowers(_)
+                | LightRain
+                | LightSleetShowers(_)
+                | LightSleet
+                | LightSnowShowers(_)
+                | LightSnow
+                | RainShowers(_)
+                | Rain
+                | SleetShowers(_)
+                | Sleet
+                | SnowShowers(_)
+                | Snow
+                | HeavyRainShowers(_)
+                | HeavyRain
+                | HeavySleetShowers(_)
+                | HeavySleet
+                | HeavySnowShowers(_)
+                | HeavySnow
+                | LightRainShowersAndThunder(_)
+                | LightRainAndThunder
+                | LightSleetShowersAndThunder(_)
+                | LightSleetAndThunder
+                | LightSnowShowersAndThunder(_)
+                | LightSnowAndThunder
+                | RainShowersAndThunder(_)
+                | RainAndThunder
+                | SleetShowersAndThunder(_)
+                | SleetAndThunder
+                | SnowShowersAndThunder(_)
+                | SnowAndThunder
+                | HeavyRainShowersAndThunder(_)
+                | HeavyRainAndThunder
+                | HeavySleetShowersAndThunder(_)
+                | HeavySleetAndThunder
+                | HeavySnowShowersAndThunder(_)
+                | HeavySnowAndThunder => {}
+            }
+        }
+        symbols
+    }
+
+    #[test]
+    fn every_symbol_has_a_bundled_icon() {
+        let symbols = all_symbols();
+        assert_eq!(symbols.len(), 21 * 3 + 20);
+
+        let missing: Vec<String> = symbols
+            .iter()
+            .map(|symbol| format!("{}.svg", symbol.code()))
+            .filter(|file| WEATHER_ICONS.get_file(file).is_none())
+            .collect();
+        assert!(missing.is_empty(), "missing icons: {missing:?}");
+    }
+
+    #[test]
+    fn codes_round_trip() {
+        for symbol in all_symbols() {
+            assert_eq!(symbol.code().parse(), Ok(symbol));
+        }
+        assert_eq!(
+            WeatherSymbol::parse_or_unknown("tornado"),
+            WeatherSymbol::Unknown
+        );
+    }
+}
@@ -0,0 +1,29 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Local, NaiveDate};
+use chrono_tz::Tz;
+use tzf_rs::DefaultFinder;
+
+/// Bundled timezone boundaries, decoded on first use.
+fn finder() -> &'static DefaultFinder {
+    static FINDER: OnceLock<DefaultFinder> = OnceLock::new();
+    FINDER.get_or_init(DefaultFinder::new)
+}
+
+/// IANA timezone at `coords`, resolved without network access.
+///
+/// `None` if the boundaries name a zone unknown to `chrono-tz`.
+pub fn at((latitude, longitude): (f64, f64)) -> Option<Tz> {
+    let name = finder().get_tz_name(longitude, latitude);
+    name.parse()
+        .map_err(|_| log::warn!("unknown timezone {:?} at {}, {}", name, latitude, longitude))
+        .ok()
+}
+
+/// Calendar date of `time` in `timezone`, or in the system timezone if `None`.
+pub fn date(time: DateTime<Local>, timezone: Option<Tz>) -> NaiveDate {
+    match timezone {
+        Some(timezone) => time.with_timezone(&timezone).date_naive(),
+        None => time.date_naive(),
+    }
+}
@@ -1,159 +1,322 @@
-use chrono::{DateTime, Local};
-use cosmic::widget::{self};
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
 use serde::{Deserialize, Serialize};
 
-use crate::app::{config::WeatherConfigState, icon_cache::WEATHER_ICONS};
+use crate::app::config::ForecastState;
+use crate::model::error::ForecastError;
+use crate::model::provider::{nws::Gridpoint, Provider};
+use crate::model::quantity::{
+    Bearing, Distance, Percentage, Precipitation, Pressure, Speed, Temperature,
+};
+use crate::model::symbol::{Variant, WeatherSymbol};
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Geometry {
-    pub r#type: String,
-    pub coordinates: Vec<f64>,
+/// Period of time a forecast value was issued for by the provider.
+///
+/// met.no, for example, switches from hourly to 6 hour steps a few days ahead.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Interval {
+    pub start: DateTime<Local>,
+    pub end: DateTime<Local>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Units {
-    pub air_pressure_at_sea_level: Option<String>,
-    pub air_temperature: Option<String>,
-    pub air_temperature_max: Option<String>,
-    pub air_temperature_min: Option<String>,
-    pub cloud_area_fraction: Option<String>,
-    pub precipitation_amount: Option<String>,
-    pub relative_humidity: Option<String>,
-    pub wind_from_direction: Option<String>,
-    pub wind_speed: Option<String>,
+impl Interval {
+    pub fn contains(&self, time: DateTime<Local>) -> bool {
+        self.start <= time && time < self.end
+    }
 }
 
+/// Instantaneous values at the start of an interval.
 #[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Meta {
-    pub updated_at: DateTime<Local>,
-    pub units: Units,
+pub struct Conditions {
+    pub air_pressure_at_sea_level: Option<Pressure>,
+    pub air_temperature: Option<Temperature>,
+    pub apparent_temperature: Option<Temperature>,
+    pub cloud_area_fraction: Option<Percentage>,
+    /// Cloud cover below about 2 km, between 2 and 6 km, and above 6 km.
+    pub cloud_area_fraction_low: Option<Percentage>,
+    pub cloud_area_fraction_medium: Option<Percentage>,
+    pub cloud_area_fraction_high: Option<Percentage>,
+    pub dew_point_temperature: Option<Temperature>,
+    pub fog_area_fraction: Option<Percentage>,
+    pub relative_humidity: Option<Percentage>,
+    pub ultraviolet_index_clear_sky: Option<f64>,
+    /// Horizontal distance at which objects can still be seen.
+    pub visibility: Option<Distance>,
+    pub wind_from_direction: Option<Bearing>,
+    pub wind_speed: Option<Speed>,
+    pub wind_speed_of_gust: Option<Speed>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Details {
-    pub air_pressure_at_sea_level: Option<f64>,
-    pub air_temperature: Option<f64>,
-    pub air_temperature_max: Option<f64>,
-    pub air_temperature_min: Option<f64>,
-    pub cloud_area_fraction: Option<f64>,
-    pub relative_humidity: Option<f64>,
-    pub wind_from_direction: Option<f64>,
-    pub wind_speed: Option<f64>,
+/// Precipitation expected in the hours after the start of a step.
+#[derive(Clone, Copy, Debug, Deserialize, Serialize, PartialEq)]
+pub struct PrecipitationWindow {
+    /// Length of the window, 1, 6 or 12.
+    pub hours: u32,
+    pub amount: Option<Precipitation>,
+    /// Likely range of the amount, if the provider reports it.
+    pub amount_min: Option<Precipitation>,
+    pub amount_max: Option<Precipitation>,
+    pub probability: Option<Percentage>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Summary {
-    pub symbol_code: String,
+/// Forecast for a single step, usually one hour.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct HourlyForecast {
+    pub interval: Interval,
+    pub conditions: Conditions,
+    pub symbol: Option<WeatherSymbol>,
+    pub probability_of_precipitation: Option<Percentage>,
+    /// Precipitation over the whole interval.
+    pub precipitation_amount: Option<Precipitation>,
+    /// Extremes over the interval, if the provider reports them.
+    pub air_temperature_max: Option<Temperature>,
+    pub air_temperature_min: Option<Temperature>,
+    /// Precipitation over the windows starting with the step, shortest first.
+    #[serde(default)]
+    pub precipitation_windows: Vec<PrecipitationWindow>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Next12Hours {
-    pub summary: Summary,
-    pub details: Option<Details>,
+impl HourlyForecast {
+    pub fn time(&self) -> DateTime<Local> {
+        self.interval.start
+    }
+
+    /// The precipitation window covering exactly this step.
+    pub fn precipitation_window(&self) -> Option<&PrecipitationWindow> {
+        let hours = (self.interval.end - self.interval.start).num_hours();
+        self.precipitation_windows
+            .iter()
+            .find(|window| i64::from(window.hours) == hours)
+    }
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Next1Hour {
-    pub summary: Summary,
-    pub details: Option<Details>,
+/// Forecast summary for a calendar day.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct DailyForecast {
+    pub date: NaiveDate,
+    pub interval: Interval,
+    pub symbol: Option<WeatherSymbol>,
+    pub air_temperature_max: Option<Temperature>,
+    pub air_temperature_min: Option<Temperature>,
+    pub probability_of_precipitation: Option<Percentage>,
+    pub precipitation_amount: Option<Precipitation>,
+    /// Likely range of the precipitation, if the provider reports one.
+    pub precipitation_amount_min: Option<Precipitation>,
+    pub precipitation_amount_max: Option<Precipitation>,
+    pub wind_speed_max: Option<Speed>,
+    pub wind_speed_of_gust_max: Option<Speed>,
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Next6Hours {
-    pub summary: Summary,
-    pub details: Option<Details>,
+impl DailyForecast {
+    /// Aggregate `hourly` into days of the calendar of `timezone`.
+    ///
+    /// Instantaneous values and interval extremes count for the day their step
+    /// starts in. Precipitation of steps spanning midnight, like the 6 hour
+    /// steps met.no switches to a few days ahead, is split by overlap. The last
+    /// day is left out if the forecast ends before it does.
+    pub fn aggregate<Tz: TimeZone>(hourly: &[HourlyForecast], timezone: &Tz) -> Vec<Self> {
+        let (Some(first), Some(end)) = (
+            hourly.first(),
+            hourly.iter().map(|forecast| forecast.interval.end).max(),
+        ) else {
+            return Vec::new();
+        };
+
+        let mut days = Vec::new();
+        let mut date = first.time().with_timezone(timezone).date_naive();
+        while let Some(next) = date.succ_opt() {
+            let interval = Interval {
+                start: start_of_day(date, timezone),
+                end: start_of_day(next, timezone),
+            };
+            if interval.start >= end || (interval.end > end && !days.is_empty()) {
+                break;
+            }
+            days.push(Self::aggregate_day(date, interval, hourly, timezone));
+            date = next;
+        }
+        days
+    }
+
+    fn aggregate_day<Tz: TimeZone>(
+        date: NaiveDate,
+        interval: Interval,
+        hourly: &[HourlyForecast],
+        timezone: &Tz,
+    ) -> Self {
+        let overlap = |step: &Interval, period: &Interval| {
+            (step.end.min(period.end) - step.start.max(period.start)).max(Duration::zero())
+        };
+        let overlapping: Vec<(&HourlyForecast, Duration)> = hourly
+            .iter()
+            .map(|forecast| (forecast, overlap(&forecast.interval, &interval)))
+            .filter(|(_, overlap)| *overlap > Duration::zero())
+            .collect();
+        let starting: Vec<&HourlyForecast> = hourly
+            .iter()
+            .filter(|forecast| interval.contains(forecast.time()))
+            .collect();
+
+        let temperatures = || {
+            starting.iter().flat_map(|forecast| {
+                [
+                    forecast.conditions.air_temperature,
+                    forecast.air_temperature_max,
+                    forecast.air_temperature_min,
+                ]
+                .into_iter()
+                .flatten()
+            })
+        };
+
+        let precipitation = |amount: fn(&HourlyForecast) -> Option<Precipitation>| {
+            overlapping
+                .iter()
+                .filter_map(|(forecast, overlap)| {
+                    let length = forecast.interval.end - forecast.interval.start;
+                    let amount = amount(forecast)?.0;
+                    Some(amount * overlap.num_seconds() as f64 / length.num_seconds().max(1) as f64)
+                })
+                .reduce(|a, b| a + b)
+                .map(Precipitation)
+        };
+
+        Self {
+            date,
+            symbol: dominant_symbol(date, &overlapping, timezone),
+            air_temperature_max: max(temperatures()),
+            air_temperature_min: min(temperatures()),
+            probability_of_precipitation: max(overlapping
+                .iter()
+                .filter_map(|(forecast, _)| forecast.probability_of_precipitation)),
+            precipitation_amount: precipitation(|forecast| forecast.precipitation_amount),
+            precipitation_amount_min: precipitation(|forecast| {
+                forecast.precipitation_window()?.amount_min
+            }),
+            precipitation_amount_max: precipitation(|forecast| {
+                forecast.precipitation_window()?.amount_max
+            }),
+            wind_speed_max: max(starting
+                .iter()
+                .filter_map(|forecast| forecast.conditions.wind_speed)),
+            wind_speed_of_gust_max: max(starting
+                .iter()
+                .filter_map(|forecast| forecast.conditions.wind_speed_of_gust)),
+            interval,
+        }
+    }
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Instant {
-    pub details: Details,
+fn max<T: PartialOrd>(values: impl Iterator<Item = T>) -> Option<T> {
+    values.reduce(|a, b| if b > a { b } else { a })
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Data {
-    pub instant: Instant,
-    pub next_12_hours: Option<Next12Hours>,
-    pub next_1_hours: Option<Next1Hour>,
-    pub next_6_hours: Option<Next6Hours>,
+fn min<T: PartialOrd>(values: impl Iterator<Item = T>) -> Option<T> {
+    values.reduce(|a, b| if b < a { b } else { a })
 }
 
-#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
-pub struct Timeseries {
-    pub time: DateTime<Local>,
-    pub data: Data,
+/// First instant of `date`, which is not midnight if a DST change skips it.
+pub fn start_of_day<Tz: TimeZone>(date: NaiveDate, timezone: &Tz) -> DateTime<Local> {
+    wall_clock(date, 0, timezone)
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
-pub struct Properties {
-    pub meta: Meta,
-    pub timeseries: Vec<Timeseries>,
+/// First instant of `date` the clocks of `timezone` show `hour` o'clock, or
+/// the next full hour if a DST change skips it.
+fn wall_clock<Tz: TimeZone>(date: NaiveDate, hour: u32, timezone: &Tz) -> DateTime<Local> {
+    (hour..24)
+        .filter_map(|hour| date.and_hms_opt(hour, 0, 0))
+        .find_map(|time| time.and_local_timezone(timezone.clone()).earliest())
+        .map(|time| time.with_timezone(&Local))
+        .unwrap_or_else(|| {
+            date.and_time(NaiveTime::MIN)
+                .and_utc()
+                .with_timezone(&Local)
+                + Duration::hours(hour.into())
+        })
 }
 
-#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+/// The most severe symbol covering at least a quarter of the daytime, or of
+/// the whole day if the forecast does not cover the daytime, drawn for day.
+fn dominant_symbol<Tz: TimeZone>(
+    date: NaiveDate,
+    overlapping: &[(&HourlyForecast, Duration)],
+    timezone: &Tz,
+) -> Option<WeatherSymbol> {
+    let daytime = Interval {
+        start: wall_clock(date, 6, timezone),
+        end: wall_clock(date, 18, timezone),
+    };
+    let overlap = |forecast: &HourlyForecast| {
+        (forecast.interval.end.min(daytime.end) - forecast.interval.start.max(daytime.start))
+            .max(Duration::zero())
+    };
+
+    let mut durations: Vec<(WeatherSymbol, Duration)> = Vec::new();
+    let daytime_covered = overlapping
+        .iter()
+        .any(|(forecast, _)| overlap(forecast) > Duration::zero());
+    for (forecast, day_overlap) in overlapping {
+        let Some(symbol) = forecast.symbol else {
+            continue;
+        };
+        let symbol = symbol.with_variant(Variant::Day);
+        let duration = if daytime_covered {
+            overlap(forecast)
+        } else {
+            *day_overlap
+        };
+        match durations.iter_mut().find(|(other, _)| *other == symbol) {
+            Some((_, total)) => *total = *total + duration,
+            None => durations.push((symbol, duration)),
+        }
+    }
+
+    let total = durations
+        .iter()
+        .fold(Duration::zero(), |total, (_, duration)| total + *duration);
+    durations
+        .iter()
+        .filter(|(_, duration)| *duration * 4 >= total && *duration > Duration::zero())
+        .map(|(symbol, _)| *symbol)
+        .max()
+}
+
+/// Provider-neutral forecast.
+///
+/// Providers convert their responses into this model, their wire formats stay
+/// private to the provider modules.
+#[derive(Clone, Debug, Deserialize, Serialize, PartialEq)]
 pub struct WeatherData {
-    pub r#type: String,
-    pub geometry: Geometry,
-    pub properties: Properties,
+    pub updated_at: DateTime<Local>,
+    /// Latitude and longitude the forecast was issued for.
+    pub coordinates: (f64, f64),
+    /// Conditions at the time of the request, if reported separately.
+    pub current: Option<HourlyForecast>,
+    pub hourly: Vec<HourlyForecast>,
+    pub daily: Vec<DailyForecast>,
 }
 
 impl WeatherData {
     pub async fn get_weather_data(
+        provider: Provider,
         coords: (f64, f64),
         last_request: Option<chrono::DateTime<chrono::Utc>>,
-    ) -> Result<Option<(WeatherConfigState, WeatherRequestStatus)>, reqwest::Error> {
-        let mut status = WeatherRequestStatus::Other;
-        let query_params = [("lat", coords.0), ("lon", coords.1)];
-
-        // note: There should not be multiple clients. The client is behind an Arc, so cloning it is cheap.
-        let mut res = reqwest::Client::new()
-            .get("https://api.met.no/weatherapi/locationforecast/2.0/complete?")
-            .header("User-Agent", "Cosmic-Ext-Weather/0.1.0");
-        if let Some(last_request) = last_request {
-            let format_str = "%a, %d %b %Y %H:%M:%S GMT";
-            res = res.header(
-                "If-Modified-Since",
-                last_request.format(format_str).to_string(),
-            )
-        }
-        let res = res.query(&query_params).send().await?;
-
-        let get_header_date = |key: &str| {
-            res.headers().get(key).and_then(|date| {
-                Some(
-                    chrono::DateTime::<chrono::FixedOffset>::parse_from_rfc2822(
-                        date.to_str().ok()?,
-                    )
-                    .ok()?,
-                )
-            })
-        };
-
-        let expires = get_header_date("Expires");
-
-        let last_request: chrono::DateTime<chrono::FixedOffset> = chrono::Utc::now().into();
-
-        let weather_ans = if res.status().as_u16() == 304 {
-            status = WeatherRequestStatus::NotModified;
-            None
-        } else {
-            let weather_ans: WeatherData = res.json().await?;
-            Some(weather_ans)
-        };
-
-        let weather_config_state = WeatherConfigState {
-            last_request: Some(last_request),
-            expires,
-            weather_data: weather_ans,
-        };
-        Ok(Some((weather_config_state, status)))
+        gridpoint: Option<Gridpoint>,
+    ) -> Result<Option<(ForecastState, WeatherRequestStatus)>, ForecastError> {
+        let data = provider
+            .get_weather_data(coords, last_request, gridpoint)
+            .await?;
+        Ok(Some(data))
     }
 
-    pub fn icon_handle(symbol: String) -> widget::icon::Handle {
-        let bytes = WEATHER_ICONS
-            .get_file(format!("{symbol}.svg"))
-            .map(|file| file.contents().to_vec())
-            .unwrap_or_default();
-        widget::icon::from_svg_bytes(bytes)
+    /// Conditions at `time`, `None` if no step of the forecast contains it.
+    ///
+    /// An outdated forecast has no current conditions rather than showing
+    /// those of a step long past.
+    pub fn current_at(&self, time: DateTime<Local>) -> Option<&HourlyForecast> {
+        self.current
+            .iter()
+            .chain(self.hourly.iter())
+            .find(|forecast| forecast.interval.contains(time))
     }
 }
 #[derive(Clone, Debug)]
@@ -161,3 +324,191 @@ pub enum WeatherRequestStatus {
     NotModified,
     Other,
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::{FixedOffset, Timelike};
+    use chrono_tz::America::Santiago;
+    use chrono_tz::Europe::Berlin;
+
+    use super::*;
+
+    /// `steps` forecasts of `hours` each from `start`, cloudy with 1 mm an
+    /// hour and the step index as temperature.
+    fn series<Tz: TimeZone>(start: DateTime<Tz>, steps: usize, hours: i64) -> Vec<HourlyForecast> {
+        let start = start.with_timezone(&Local);
+        (0..steps)
+            .map(|step| {
+                let start = start + Duration::hours(step as i64 * hours);
+                HourlyForecast {
+                    interval: Interval {
+                        start,
+                        end: start + Duration::hours(hours),
+                    },
+                    conditions: Conditions {
+                        air_temperature: Some(Temperature(step as f64)),
+                        ..Default::default()
+                    },
+                    symbol: Some(WeatherSymbol::Cloudy),
+                    probability_of_precipitation: None,
+                    precipitation_amount: Some(Precipitation(hours as f64)),
+                    air_temperature_max: None,
+                    air_temperature_min: None,
+                    precipitation_windows: Vec::new(),
+                }
+            })
+            .collect()
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn hours(interval: &Interval) -> i64 {
+        (interval.end - interval.start).num_hours()
+    }
+
+    #[test]
+    fn aggregate_half_hour_offset_east() {
+        let india = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        let hourly = series(india.with_ymd_and_hms(2024, 6, 1, 0, 0, 0).unwrap(), 48, 1);
+
+        let daily = DailyForecast::aggregate(&hourly, &india);
+
+        assert_eq!(daily.len(), 2);
+        assert_eq!(daily[0].date, date(2024, 6, 1));
+        assert_eq!(daily[1].date, date(2024, 6, 2));
+        assert_eq!(
+            daily[1].interval.start,
+            india.with_ymd_and_hms(2024, 6, 2, 0, 0, 0).unwrap()
+        );
+        assert_eq!(daily[0].air_temperature_min, Some(Temperature(0.0)));
+        assert_eq!(daily[0].air_temperature_max, Some(Temperature(23.0)));
+        assert_eq!(daily[1].air_temperature_min, Some(Temperature(24.0)));
+        assert_eq!(daily[1].precipitation_amount, Some(Precipitation(24.0)));
+    }
+
+    #[test]
+    fn aggregate_half_hour_offset_west() {
+        let newfoundland = FixedOffset::west_opt(3 * 3600 + 1800).unwrap();
+        // Hourly until 18:00, then 6 hour steps spanning midnight
+        let mut hourly = series(
+            newfoundland.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap(),
+            18,
+            1,
+        );
+        hourly.extend(series(
+            newfoundland
+                .with_ymd_and_hms(2024, 1, 10, 18, 0, 0)
+                .unwrap(),
+            6,
+            6,
+        ));
+
+        let daily = DailyForecast::aggregate(&hourly, &newfoundland);
+
+        assert_eq!(daily.len(), 2);
+        assert_eq!(
+            daily[0].interval.start,
+            newfoundland.with_ymd_and_hms(2024, 1, 10, 0, 0, 0).unwrap()
+        );
+        // 18 hourly steps, then 18:00 to 24:00 of the first 6 hour step
+        assert_eq!(daily[0].precipitation_amount, Some(Precipitation(24.0)));
+        assert_eq!(daily[1].date, date(2024, 1, 11));
+        assert_eq!(daily[1].precipitation_amount, Some(Precipitation(24.0)));
+        // Instantaneous values count for the day the step starts in
+        assert_eq!(daily[1].air_temperature_min, Some(Temperature(1.0)));
+    }
+
+    #[test]
+    fn aggregate_across_dst_changes() {
+        let spring = series(
+            Berlin.with_ymd_and_hms(2024, 3, 30, 0, 0, 0).unwrap(),
+            71,
+            1,
+        );
+        let daily = DailyForecast::aggregate(&spring, &Berlin);
+        assert_eq!(
+            daily
+                .iter()
+                .map(|day| hours(&day.interval))
+                .collect::<Vec<_>>(),
+            [24, 23, 24]
+        );
+        assert_eq!(daily[1].date, date(2024, 3, 31));
+        assert_eq!(daily[1].precipitation_amount, Some(Precipitation(23.0)));
+        assert_eq!(
+            daily[2].interval.start,
+            Berlin.with_ymd_and_hms(2024, 4, 1, 0, 0, 0).unwrap()
+        );
+
+        let autumn = series(
+            Berlin.with_ymd_and_hms(2024, 10, 26, 0, 0, 0).unwrap(),
+            73,
+            1,
+        );
+        let daily = DailyForecast::aggregate(&autumn, &Berlin);
+        assert_eq!(
+            daily
+                .iter()
+                .map(|day| hours(&day.interval))
+                .collect::<Vec<_>>(),
+            [24, 25, 24]
+        );
+        assert_eq!(daily[1].precipitation_amount, Some(Precipitation(25.0)));
+        assert_eq!(daily[2].air_temperature_min, Some(Temperature(49.0)));
+    }
+
+    #[test]
+    fn aggregate_skips_missing_midnight() {
+        // Chile moves the clocks forward from 00:00 to 01:00
+        let start = Santiago.with_ymd_and_hms(2022, 9, 10, 0, 0, 0).unwrap();
+        let daily = DailyForecast::aggregate(&series(start, 47, 1), &Santiago);
+
+        assert_eq!(daily[1].date, date(2022, 9, 11));
+        assert_eq!(hours(&daily[1].interval), 23);
+        assert_eq!(
+            daily[1].interval.start,
+            Santiago.with_ymd_and_hms(2022, 9, 11, 1, 0, 0).unwrap()
+        );
+    }
+
+    #[test]
+    fn dominant_symbol_uses_local_daytime_on_dst_days() {
+        // Rain from 06:00 to 09:00 is a quarter of the daytime, but only if the
+        // daytime starts at 06:00 on the clock and not 6 hours after midnight
+        for day in [31, 30] {
+            let mut hourly = series(
+                Berlin.with_ymd_and_hms(2024, 3, day, 0, 0, 0).unwrap(),
+                24,
+                1,
+            );
+            for forecast in &mut hourly {
+                let hour = forecast.time().with_timezone(&Berlin).hour();
+                if (6..9).contains(&hour) {
+                    forecast.symbol = Some(WeatherSymbol::Rain);
+                }
+            }
+
+            let daily = DailyForecast::aggregate(&hourly, &Berlin);
+
+            assert_eq!(daily[0].symbol, Some(WeatherSymbol::Rain), "March {day}");
+        }
+    }
+
+    #[test]
+    fn dominant_symbol_outside_daytime() {
+        let start = Berlin.with_ymd_and_hms(2024, 6, 1, 20, 0, 0).unwrap();
+        let mut hourly = series(start, 4, 1);
+        for forecast in &mut hourly {
+            forecast.symbol = Some(WeatherSymbol::PartlyCloudy(Variant::Night));
+        }
+
+        let daily = DailyForecast::aggregate(&hourly, &Berlin);
+
+        assert_eq!(
+            daily[0].symbol,
+            Some(WeatherSymbol::PartlyCloudy(Variant::Day))
+        );
+    }
+}
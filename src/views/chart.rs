@@ -0,0 +1,420 @@
+use chrono::{Duration, Local};
+use chrono_tz::Tz;
+use cosmic::iced::alignment::{Horizontal, Vertical};
+use cosmic::iced::widget::canvas::{self, Frame, Geometry, LineDash, Path, Stroke, Text};
+use cosmic::iced::{mouse, Color, Length, Point, Rectangle, Size};
+use cosmic::{Element, Renderer, Theme};
+
+use crate::app::config::PrecipitationUnits;
+use crate::app::{App, Message};
+use crate::fl;
+use crate::model::quantity::Precipitation;
+use crate::model::weather::WeatherData;
+
+/// Hours ahead shown in the chart.
+const CHART_HOURS: i64 = 48;
+/// Hours between two time labels.
+const LABEL_HOURS: usize = 6;
+/// Height of the chart in pixels.
+const CHART_HEIGHT: f32 = 180.0;
+/// Space for the axis labels around the plot.
+const MARGIN_SIDE: f32 = 44.0;
+const MARGIN_TOP: f32 = 8.0;
+const MARGIN_BOTTOM: f32 = 22.0;
+const TEXT_SIZE: f32 = 12.0;
+/// Smallest precipitation at the top of the chart, so drizzle does not fill
+/// the whole height.
+const MIN_PRECIPITATION_SCALE: Precipitation = Precipitation(2.0);
+
+/// Values of one hour in the configured units.
+struct Sample {
+    time: String,
+    temperature: Option<f64>,
+    apparent_temperature: Option<f64>,
+    precipitation: Option<f64>,
+    cloud_cover: Option<f64>,
+}
+
+/// Temperature, feels-like and precipitation of the next [`CHART_HOURS`]
+/// with cloud cover shaded in the background.
+struct HourlyChart {
+    samples: Vec<Sample>,
+    temperature_units: &'static str,
+    precipitation_units: PrecipitationUnits,
+}
+
+impl HourlyChart {
+    /// Plot area inside `bounds`, without the axis labels.
+    fn plot(size: Size) -> Rectangle {
+        Rectangle {
+            x: MARGIN_SIDE,
+            y: MARGIN_TOP,
+            width: (size.width - 2.0 * MARGIN_SIDE).max(1.0),
+            height: (size.height - MARGIN_TOP - MARGIN_BOTTOM).max(1.0),
+        }
+    }
+
+    /// Width of the column of one hour.
+    fn step(&self, plot: Rectangle) -> f32 {
+        plot.width / self.samples.len().max(1) as f32
+    }
+
+    /// Index of the hour below `position`, if it is inside the plot.
+    fn hovered(&self, plot: Rectangle, position: Point) -> Option<usize> {
+        if !plot.contains(position) {
+            return None;
+        }
+        let index = ((position.x - plot.x) / self.step(plot)) as usize;
+        (index < self.samples.len()).then_some(index)
+    }
+
+    /// Lowest and highest temperature, padded so the lines do not touch the edges.
+    fn temperature_range(&self) -> (f64, f64) {
+        let (min, max) = self
+            .samples
+            .iter()
+            .flat_map(|sample| [sample.temperature, sample.apparent_temperature])
+            .flatten()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), value| {
+                (min.min(value), max.max(value))
+            });
+        if min > max {
+            (0.0, 1.0)
+        } else {
+            (min.floor() - 1.0, max.ceil() + 1.0)
+        }
+    }
+
+    fn precipitation_scale(&self) -> f64 {
+        self.samples
+            .iter()
+            .filter_map(|sample| sample.precipitation)
+            .fold(
+                self.precipitation_units.convert(MIN_PRECIPITATION_SCALE),
+                f64::max,
+            )
+    }
+
+    /// Line through `value` of every hour, interrupted where it is missing.
+    fn line(
+        &self,
+        plot: Rectangle,
+        (min, max): (f64, f64),
+        value: impl Fn(&Sample) -> Option<f64>,
+    ) -> Path {
+        let step = self.step(plot);
+        Path::new(|builder| {
+            let mut drawing = false;
+            for (index, sample) in self.samples.iter().enumerate() {
+                let Some(value) = value(sample) else {
+                    drawing = false;
+                    continue;
+                };
+                let point = Point::new(
+                    plot.x + (index as f32 + 0.5) * step,
+                    plot.y + plot.height * ((max - value) / (max - min)) as f32,
+                );
+                if drawing {
+                    builder.line_to(point);
+                } else {
+                    builder.move_to(point);
+                    drawing = true;
+                }
+            }
+        })
+    }
+
+    fn text(content: String, position: Point, color: Color, horizontal: Horizontal) -> Text {
+        Text {
+            content,
+            position,
+            color,
+            size: TEXT_SIZE.into(),
+            horizontal_alignment: horizontal,
+            vertical_alignment: Vertical::Center,
+            ..Text::default()
+        }
+    }
+
+    /// Values of the hour at `index` in a box next to `position`.
+    fn draw_tooltip(
+        &self,
+        frame: &mut Frame,
+        theme: &Theme,
+        plot: Rectangle,
+        index: usize,
+        position: Point,
+    ) {
+        let cosmic = theme.cosmic();
+        let text_color = Color::from(cosmic.background.on);
+        let sample = &self.samples[index];
+
+        let step = self.step(plot);
+        let x = plot.x + (index as f32 + 0.5) * step;
+        frame.stroke(
+            &Path::line(Point::new(x, plot.y), Point::new(x, plot.y + plot.height)),
+            Stroke::default()
+                .with_color(Color {
+                    a: 0.5,
+                    ..text_color
+                })
+                .with_width(1.0),
+        );
+
+        let mut lines = vec![sample.time.clone()];
+        if let Some(temperature) = sample.temperature {
+            lines.push(format!(
+                "{}: {:.0}{}",
+                fl!("chart-temperature"),
+                temperature,
+                self.temperature_units
+            ));
+        }
+        if let Some(apparent_temperature) = sample.apparent_temperature {
+            lines.push(format!(
+                "{}: {:.0}{}",
+                fl!("chart-feels-like"),
+                apparent_temperature,
+                self.temperature_units
+            ));
+        }
+        if let Some(precipitation) = sample.precipitation {
+            lines.push(format!(
+                "{}: {:.*} {}",
+                fl!("chart-precipitation"),
+                self.precipitation_units.precision(),
+                precipitation,
+                self.precipitation_units.label()
+            ));
+        }
+        if let Some(cloud_cover) = sample.cloud_cover {
+            lines.push(format!(
+                "{}: {:.0} %",
+                fl!("chart-cloud-cover"),
+                cloud_cover
+            ));
+        }
+
+        // Glyph widths are not known while drawing, so the box is sized by
+        // the longest line at an average character width.
+        let padding = 6.0;
+        let line_height = TEXT_SIZE * 1.4;
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0) as f32
+            * TEXT_SIZE
+            * 0.6
+            + 2.0 * padding;
+        let height = lines.len() as f32 * line_height + 2.0 * padding;
+
+        // Keep the box inside the chart, on the side away from the edge
+        let left = if position.x + 12.0 + width > frame.width() {
+            position.x - 12.0 - width
+        } else {
+            position.x + 12.0
+        };
+        let top = position.y.clamp(0.0, (frame.height() - height).max(0.0));
+
+        let background = Path::rectangle(Point::new(left, top), Size::new(width, height));
+        frame.fill(&background, Color::from(cosmic.background.component.base));
+        frame.stroke(
+            &background,
+            Stroke::default()
+                .with_color(Color::from(cosmic.background.component.divider))
+                .with_width(1.0),
+        );
+        for (row, line) in lines.into_iter().enumerate() {
+            frame.fill_text(Self::text(
+                line,
+                Point::new(
+                    left + padding,
+                    top + padding + (row as f32 + 0.5) * line_height,
+                ),
+                text_color,
+                Horizontal::Left,
+            ));
+        }
+    }
+}
+
+impl canvas::Program<Message, Theme, Renderer> for HourlyChart {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        theme: &Theme,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> Vec<Geometry> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        if self.samples.is_empty() {
+            return vec![frame.into_geometry()];
+        }
+
+        let cosmic = theme.cosmic();
+        let text_color = Color::from(cosmic.background.on);
+        let accent = Color::from(cosmic.accent_color());
+        let precipitation_color = Color {
+            a: 0.6,
+            ..Color::from(cosmic.palette.accent_blue)
+        };
+
+        let plot = Self::plot(bounds.size());
+        let step = self.step(plot);
+
+        // Cloud cover darkens the background of each hour
+        for (index, sample) in self.samples.iter().enumerate() {
+            if let Some(cloud_cover) = sample.cloud_cover {
+                frame.fill_rectangle(
+                    Point::new(plot.x + index as f32 * step, plot.y),
+                    Size::new(step, plot.height),
+                    Color {
+                        a: 0.25 * (cloud_cover / 100.0).clamp(0.0, 1.0) as f32,
+                        ..text_color
+                    },
+                );
+            }
+        }
+
+        // Precipitation bars grow from the bottom, scaled to the right axis
+        let precipitation_scale = self.precipitation_scale();
+        for (index, sample) in self.samples.iter().enumerate() {
+            if let Some(precipitation) = sample.precipitation.filter(|value| *value > 0.0) {
+                let height = plot.height * (precipitation / precipitation_scale) as f32;
+                frame.fill_rectangle(
+                    Point::new(
+                        plot.x + index as f32 * step + step * 0.15,
+                        plot.y + plot.height - height,
+                    ),
+                    Size::new(step * 0.7, height),
+                    precipitation_color,
+                );
+            }
+        }
+
+        let range = self.temperature_range();
+        frame.stroke(
+            &self.line(plot, range, |sample| sample.apparent_temperature),
+            Stroke {
+                line_dash: LineDash {
+                    segments: &[4.0, 4.0],
+                    offset: 0,
+                },
+                ..Stroke::default().with_color(accent).with_width(1.5)
+            },
+        );
+        frame.stroke(
+            &self.line(plot, range, |sample| sample.temperature),
+            Stroke::default().with_color(accent).with_width(2.5),
+        );
+
+        // Axes: temperature on the left, precipitation on the right, time below
+        let (min, max) = range;
+        for (value, y) in [(max, plot.y), (min, plot.y + plot.height)] {
+            frame.fill_text(Self::text(
+                format!("{:.0}{}", value, self.temperature_units),
+                Point::new(plot.x - 6.0, y),
+                text_color,
+                Horizontal::Right,
+            ));
+        }
+        frame.fill_text(Self::text(
+            format!(
+                "{:.*} {}",
+                self.precipitation_units.precision(),
+                precipitation_scale,
+                self.precipitation_units.label()
+            ),
+            Point::new(plot.x + plot.width + 6.0, plot.y),
+            precipitation_color,
+            Horizontal::Left,
+        ));
+        for (index, sample) in self.samples.iter().enumerate().step_by(LABEL_HOURS) {
+            frame.fill_text(Self::text(
+                sample.time.clone(),
+                Point::new(
+                    plot.x + (index as f32 + 0.5) * step,
+                    plot.y + plot.height + MARGIN_BOTTOM / 2.0,
+                ),
+                text_color,
+                Horizontal::Center,
+            ));
+        }
+
+        if let Some(position) = cursor.position_in(bounds) {
+            if let Some(index) = self.hovered(plot, position) {
+                self.draw_tooltip(&mut frame, theme, plot, index, position);
+            }
+        }
+
+        vec![frame.into_geometry()]
+    }
+
+    fn mouse_interaction(
+        &self,
+        _state: &Self::State,
+        bounds: Rectangle,
+        cursor: mouse::Cursor,
+    ) -> mouse::Interaction {
+        match cursor.position_in(bounds) {
+            Some(position) if self.hovered(Self::plot(bounds.size()), position).is_some() => {
+                mouse::Interaction::Crosshair
+            }
+            _ => mouse::Interaction::default(),
+        }
+    }
+}
+
+impl App
+where
+    Self: cosmic::Application,
+{
+    /// Chart of the next [`CHART_HOURS`] of `weather_data`, with times in
+    /// `timezone`.
+    pub fn view_hourly_chart<'a>(
+        &self,
+        weather_data: &WeatherData,
+        timezone: Option<Tz>,
+    ) -> Element<'a, Message> {
+        let now = Local::now();
+        let units = self.config.units;
+        let precipitation_units = self.config.precipitation_units;
+        let samples = weather_data
+            .hourly
+            .iter()
+            .filter(|forecast| forecast.interval.end > now)
+            .take_while(|forecast| forecast.time() < now + Duration::hours(CHART_HOURS))
+            .map(|forecast| Sample {
+                time: self.format_time(forecast, timezone).trim().to_string(),
+                temperature: forecast
+                    .conditions
+                    .air_temperature
+                    .map(|temperature| units.convert(temperature)),
+                apparent_temperature: forecast
+                    .conditions
+                    .apparent_temperature
+                    .map(|temperature| units.convert(temperature)),
+                precipitation: forecast
+                    .precipitation_amount
+                    .map(|precipitation| precipitation_units.convert(precipitation)),
+                cloud_cover: forecast
+                    .conditions
+                    .cloud_area_fraction
+                    .map(|cloud_cover| cloud_cover.0),
+            })
+            .collect();
+
+        canvas::Canvas::new(HourlyChart {
+            samples,
+            temperature_units: units.label(),
+            precipitation_units,
+        })
+        .width(Length::Fill)
+        .height(Length::Fixed(CHART_HEIGHT))
+        .into()
+    }
+}
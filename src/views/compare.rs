@@ -0,0 +1,126 @@
+use chrono::{Duration, Local};
+use cosmic::iced::{Alignment, Length};
+use cosmic::prelude::CollectionWidget;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::config::MAX_COMPARED_LOCATIONS;
+use crate::app::{App, Message};
+use crate::fl;
+
+impl App
+where
+    Self: cosmic::Application,
+{
+    pub fn view_compare_forecast(&self) -> Element<Message> {
+        let current_time = Local::now();
+        let spacing = cosmic::theme::active().cosmic().spacing;
+
+        if self.config.locations.len() < 2 {
+            return widget::text(fl!("compare-needs-locations")).into();
+        }
+
+        let compared = self.config.compared_locations();
+        let choices = self.config.locations.iter().enumerate().fold(
+            widget::settings::view_section(fl!("compared-locations")),
+            |section, (index, location)| {
+                section.add(
+                    widget::settings::item::builder(location.name.clone())
+                        .toggler(compared.contains(&location), move |compared| {
+                            Message::CompareLocation(index, compared)
+                        }),
+                )
+            },
+        );
+
+        let columns: Vec<Element<Message>> =
+            compared
+                .iter()
+                .map(|location| {
+                    let column = widget::column()
+                        .spacing(spacing.space_xs)
+                        .width(Length::Fill)
+                        .push(widget::text::title4(location.name.clone()));
+
+                    let Some((forecast_state, weather_data)) =
+                        self.config_state.forecasts.get(&location.key()).and_then(
+                            |forecast_state| {
+                                Some((forecast_state, forecast_state.weather_data.as_ref()?))
+                            },
+                        )
+                    else {
+                        return column.push(widget::text("No weather data")).into();
+                    };
+                    let current = weather_data.current_at(current_time);
+                    let timezone = self.config.timezone(location);
+
+                    let hours = weather_data
+                        .hourly
+                        .iter()
+                        .filter(|forecast| {
+                            forecast.time() >= current_time
+                                && forecast.time() < current_time + Duration::hours(24)
+                        })
+                        .fold(
+                            widget::column().spacing(spacing.space_xxs),
+                            |hours, forecast| {
+                                hours.push(
+                                    widget::row()
+                                        .align_items(Alignment::Center)
+                                        .spacing(spacing.space_xs)
+                                        .push(
+                                            widget::text(self.format_time(forecast, timezone))
+                                                .width(Length::Fixed(80.0)),
+                                        )
+                                        .push_maybe(
+                                            forecast
+                                                .symbol
+                                                .map(|symbol| self.view_symbol_icon(symbol, 24)),
+                                        )
+                                        .push_maybe(forecast.conditions.air_temperature.map(
+                                            |temperature| self.view_temperature(temperature, 16),
+                                        )),
+                                )
+                            },
+                        );
+
+                    column
+                        .push(
+                            widget::row()
+                                .align_items(Alignment::Center)
+                                .spacing(spacing.space_xs)
+                                .push_maybe(
+                                    current
+                                        .and_then(|current| current.symbol)
+                                        .map(|symbol| self.view_symbol_icon(symbol, 64)),
+                                )
+                                .push_maybe(
+                                    current
+                                        .and_then(|current| current.conditions.air_temperature)
+                                        .map(|temperature| self.view_temperature(temperature, 32)),
+                                ),
+                        )
+                        .push_maybe(
+                            current
+                                .and_then(|current| current.symbol)
+                                .map(|symbol| widget::text(symbol.description())),
+                        )
+                        .push(self.view_forecast_age(forecast_state, timezone))
+                        .push(hours)
+                        .into()
+                })
+                .collect();
+
+        widget::column()
+            .padding(spacing.space_xs)
+            .spacing(spacing.space_xs)
+            .push(widget::row::with_children(columns).spacing(spacing.space_m))
+            .push(choices)
+            .push(widget::text::caption(fl!(
+                "compared-locations-description",
+                count = MAX_COMPARED_LOCATIONS
+            )))
+            .push(widget::text(self.config.provider.attribution()))
+            .into()
+    }
+}
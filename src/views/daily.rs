@@ -1,4 +1,3 @@
-use chrono::DateTime;
 use chrono::Local;
 use cosmic::iced::Alignment;
 use cosmic::iced_widget::scrollable::Direction;
@@ -7,11 +6,9 @@ use cosmic::prelude::CollectionWidget;
 use cosmic::widget;
 use cosmic::Element;
 
-use crate::app::config::TimeFmt;
 use crate::app::{App, Message};
-use crate::model::weather::Details;
-use crate::model::weather::Timeseries;
-use crate::model::weather::WeatherData;
+use crate::fl;
+use crate::model::weather::DailyForecast;
 
 impl App
 where
@@ -19,79 +16,19 @@ where
 {
     pub fn view_daily_forecast(&self) -> Element<Message> {
         let current_time = Local::now();
-        let location = self.config.location.clone();
+        let location = self.config.location().map(|location| location.name.clone());
         let spacing = cosmic::theme::active().cosmic().spacing;
-        let Some(weather_data) = &self.config_state.weather_data else {
+        let Some(weather_data) = self.weather_data() else {
             return cosmic::widget::text("No weather data").into();
         };
-        let data = weather_data
-            .properties
-            .timeseries
-            .iter()
-            .min_by_key(|timeseries| (timeseries.time - current_time).num_seconds().abs())
-            .map(|ts| ts.data.clone())
-            .unwrap_or_default();
-
-        let last_updated = match self.config.timefmt {
-            TimeFmt::TwelveHr => weather_data
-                .properties
-                .meta
-                .updated_at
-                .format("%_I:%M %p")
-                .to_string(),
-            TimeFmt::TwentyFourHr => weather_data
-                .properties
-                .meta
-                .updated_at
-                .format("%_H:%M")
-                .to_string(),
-        };
-
-        // For data logging purposes
-        for ts in weather_data.properties.timeseries.iter() {
-            println!("{}", ts.time);
-        }
+        let current = weather_data.current_at(current_time);
 
-        let timeseries: Vec<Element<Message>> =
-            weather_data
-                .properties
-                .timeseries
-                .iter()
-                .filter(|timeseries| self.check_time(timeseries, current_time))
-                .map(|ts| {
-                    let data_6_hrs = match &ts.data.next_6_hours {
-                        Some(data) => match &data.details {
-                            Some(details) => details,
-                            None => &Details::default()
-                        },
-                        None => &Details::default()
-                    };
-
-                    widget::column()
-                        .align_items(Alignment::Center)
-                        .padding(spacing.space_xs)
-                        .spacing(spacing.space_xs)
-                        .push(widget::text(self.format_date(ts)))
-                        .push_maybe(ts.data.next_12_hours.as_ref().map(|next_12_hours| {
-                            let symbol = next_12_hours.summary.symbol_code.clone();
-                            widget::icon(WeatherData::icon_handle(symbol)).size(50)
-                        }))
-                        .push_maybe(data_6_hrs.air_temperature_max.map(
-                            |air_temperature_max| {
-                                widget::text(format!("{}°", self.set_temp_units(air_temperature_max)))
-                                    .size(24)
-                                    .style(cosmic::style::Text::Accent)
-                            },
-                        ))
-                        .push_maybe(data_6_hrs.air_temperature_min.map(
-                            |air_temperature_min| {
-                                widget::text(format!("{}°", self.set_temp_units(air_temperature_min)))
-                                    .size(24)
-                            },
-                        ))
-                        .into()
-                })
-                .collect();
+        let timeseries: Vec<Element<Message>> = weather_data
+            .daily
+            .iter()
+            .filter(|day| day.interval.end > current_time)
+            .map(|day| self.view_day(day))
+            .collect();
 
         let column = widget::column()
             .padding(spacing.space_xs)
@@ -99,10 +36,11 @@ where
             .push(
                 widget::row()
                     .spacing(spacing.space_m)
-                    .push_maybe(data.next_1_hours.as_ref().map(|next_1_hours| {
-                        let symbol = next_1_hours.summary.symbol_code.clone();
-                        widget::icon(WeatherData::icon_handle(symbol)).size(150)
-                    }))
+                    .push_maybe(
+                        current
+                            .and_then(|current| current.symbol)
+                            .map(|symbol| widget::icon(symbol.icon_handle()).size(150)),
+                    )
                     .push(
                         widget::column()
                             .spacing(spacing.space_xs)
@@ -111,53 +49,76 @@ where
                                     .map(widget::text::title4)
                                     .unwrap_or(widget::text::title4("Unknown location")),
                             )
-                            .push_maybe(data.instant.details.air_temperature.map(
-                                |air_temperature| {
-                                    widget::text(format!(
-                                        "{}°",
-                                        self.set_temp_units(air_temperature)
-                                    ))
-                                    .size(42)
-                                    .style(cosmic::style::Text::Accent)
-                                },
-                            )),
+                            .push_maybe(
+                                current
+                                    .is_none()
+                                    .then(|| widget::text(fl!("no-current-conditions"))),
+                            )
+                            .push_maybe(
+                                current
+                                    .and_then(|current| current.symbol)
+                                    .map(|symbol| widget::text(symbol.description())),
+                            )
+                            .push_maybe(
+                                current
+                                    .and_then(|current| current.conditions.air_temperature)
+                                    .map(|air_temperature| {
+                                        widget::text(format!(
+                                            "{}°",
+                                            self.set_temp_units(air_temperature)
+                                        ))
+                                        .size(42)
+                                        .style(cosmic::style::Text::Accent)
+                                    }),
+                            ),
                     ),
             )
+            .push_maybe(
+                self.forecast_state()
+                    .map(|forecast_state| self.view_forecast_age(forecast_state, self.timezone())),
+            )
             .push(
                 widget::scrollable(widget::row::with_children(timeseries))
                     .direction(Direction::Horizontal(Properties::default())),
             )
-            .push(widget::text(format!("Last updated: {}", last_updated)))
-            .push(widget::text(
-                "Weather data from the Norwegian Meteorological Institute",
-            ));
+            .push(widget::text(self.config.provider.attribution()));
 
         column.into()
     }
 
-    fn format_date(&self, ts: &Timeseries) -> String {
-        ts.time.format("%a").to_string()
+    /// Date, symbol, temperature range and precipitation of `day`.
+    pub fn view_day<'a>(&self, day: &DailyForecast) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+        widget::column()
+            .align_items(Alignment::Center)
+            .padding(spacing.space_xs)
+            .spacing(spacing.space_xs)
+            .push(widget::text(self.format_date(day)))
+            .push_maybe(
+                day.symbol
+                    .map(|symbol| widget::icon(symbol.icon_handle()).size(50)),
+            )
+            .push_maybe(day.air_temperature_max.map(|air_temperature_max| {
+                widget::text(format!("{}°", self.set_temp_units(air_temperature_max)))
+                    .size(24)
+                    .style(cosmic::style::Text::Accent)
+            }))
+            .push_maybe(day.air_temperature_min.map(|air_temperature_min| {
+                widget::text(format!("{}°", self.set_temp_units(air_temperature_min))).size(24)
+            }))
+            .push_maybe(
+                self.format_precipitation(
+                    day.precipitation_amount,
+                    day.precipitation_amount_min,
+                    day.precipitation_amount_max,
+                    day.probability_of_precipitation,
+                )
+                .map(widget::text::caption),
+            )
+            .into()
     }
 
-    fn check_time(&self, timeseries: &Timeseries, current_time: DateTime<Local>) -> bool {
-        let timezone = current_time
-            .format("%:::z")
-            .to_string()
-            .parse::<i64>()
-            .unwrap_or_default();
-        let timehour = timeseries
-            .time
-            .format("%H")
-            .to_string()
-            .parse::<i64>()
-            .unwrap_or_default();
-        let comparetime = if (12 + timezone) > 12 {
-            timezone
-        } else {
-            timezone + 12
-        };
-
-        timeseries.time > current_time
-        && timehour == comparetime
+    fn format_date(&self, day: &DailyForecast) -> String {
+        day.date.format("%a").to_string()
     }
 }
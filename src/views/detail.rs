@@ -3,11 +3,11 @@ use cosmic::prelude::CollectionWidget;
 use cosmic::widget;
 use cosmic::Element;
 
-use crate::app::config::PressureUnits;
 use crate::app::config::SpeedUnits;
-use crate::app::config::TimeFmt;
 use crate::app::{App, Message};
-use crate::model::weather::WeatherData;
+use crate::fl;
+use crate::model::provider::Field;
+use crate::model::quantity::{Distance, Percentage, Pressure, Speed};
 
 impl App
 where
@@ -15,46 +15,160 @@ where
 {
     pub fn view_detail_forecast(&self) -> Element<Message> {
         let current_time = Local::now();
-        let location = self.config.location.clone();
+        let location = self.config.location().map(|location| location.name.clone());
         let spacing = cosmic::theme::active().cosmic().spacing;
-        let Some(weather_data) = &self.config_state.weather_data else {
+        let Some(weather_data) = self.weather_data() else {
             return cosmic::widget::text("No weather data").into();
         };
-        let data = weather_data
-            .properties
-            .timeseries
-            .iter()
-            .min_by_key(|timeseries| (timeseries.time - current_time).num_seconds().abs())
-            .map(|ts| ts.data.clone())
+        let current = weather_data.current_at(current_time);
+        let conditions = current
+            .map(|current| current.conditions.clone())
             .unwrap_or_default();
 
-        let last_updated = match self.config.timefmt {
-            TimeFmt::TwelveHr => weather_data
-                .properties
-                .meta
-                .updated_at
-                .format("%_I:%M %p")
-                .to_string(),
-            TimeFmt::TwentyFourHr => weather_data
-                .properties
-                .meta
-                .updated_at
-                .format("%_H:%M")
-                .to_string(),
+        let pressure_units = self.config.pressure_units.label();
+        let speed_units = self.config.speed_units.label();
+        let percentage = |value: Option<Percentage>| value.map(|value| format!("{:.0} %", value.0));
+        let speed = |value: Option<Speed>| {
+            value.map(|value| format!("{:.1} {}", self.calculate_speed_units(value), speed_units))
         };
 
-        let pressure_units = match self.config.pressure_units {
-            PressureUnits::Hectopascal => "hPa".to_string(),
-            PressureUnits::Bar => "bar".to_string(),
-            PressureUnits::Kilopascal => "kPa".to_string(),
-            PressureUnits::Psi => "psi".to_string(),
-        };
+        let rows = [
+            (
+                Field::AirPressure,
+                fl!("air-pressure"),
+                fl!("air-pressure-description"),
+                conditions.air_pressure_at_sea_level.map(|value| {
+                    format!(
+                        "{:.1} {}",
+                        self.calculate_pressure_units(value),
+                        pressure_units
+                    )
+                }),
+            ),
+            (
+                Field::CloudAreaFraction,
+                fl!("cloud-cover"),
+                fl!("cloud-cover-description"),
+                percentage(conditions.cloud_area_fraction),
+            ),
+            (
+                Field::CloudAreaFractionLayers,
+                fl!("cloud-cover-low"),
+                fl!("cloud-cover-low-description"),
+                percentage(conditions.cloud_area_fraction_low),
+            ),
+            (
+                Field::CloudAreaFractionLayers,
+                fl!("cloud-cover-medium"),
+                fl!("cloud-cover-medium-description"),
+                percentage(conditions.cloud_area_fraction_medium),
+            ),
+            (
+                Field::CloudAreaFractionLayers,
+                fl!("cloud-cover-high"),
+                fl!("cloud-cover-high-description"),
+                percentage(conditions.cloud_area_fraction_high),
+            ),
+            (
+                Field::FogAreaFraction,
+                fl!("fog"),
+                fl!("fog-description"),
+                percentage(conditions.fog_area_fraction),
+            ),
+            (
+                Field::RelativeHumidity,
+                fl!("relative-humidity"),
+                fl!("relative-humidity-description"),
+                percentage(conditions.relative_humidity),
+            ),
+            (
+                Field::DewPointTemperature,
+                fl!("dew-point"),
+                fl!("dew-point-description"),
+                conditions
+                    .dew_point_temperature
+                    .map(|value| format!("{}°", self.set_temp_units(value))),
+            ),
+            (
+                Field::WindFromDirection,
+                fl!("wind-direction"),
+                fl!("wind-direction-description"),
+                conditions
+                    .wind_from_direction
+                    .map(|value| format!("{:.0}°", value.0)),
+            ),
+            (
+                Field::WindSpeed,
+                fl!("wind-speed"),
+                fl!("wind-speed-description"),
+                speed(conditions.wind_speed),
+            ),
+            (
+                Field::WindSpeedOfGust,
+                fl!("wind-gusts"),
+                fl!("wind-gusts-description"),
+                speed(conditions.wind_speed_of_gust),
+            ),
+            (
+                Field::UltravioletIndex,
+                fl!("uv-index"),
+                fl!("uv-index-description"),
+                conditions.ultraviolet_index_clear_sky.map(|index| {
+                    fl!(
+                        "uv-index-value",
+                        index = format!("{:.0}", index),
+                        level = uv_index_level(index)
+                    )
+                }),
+            ),
+            (
+                Field::Visibility,
+                fl!("visibility"),
+                fl!("visibility-description"),
+                conditions
+                    .visibility
+                    .map(|value| self.format_distance(value)),
+            ),
+        ];
 
-        let speed_units = match self.config.speed_units {
-            SpeedUnits::MetersPerSecond => "m/s".to_string(),
-            SpeedUnits::MilesPerHour => "mph".to_string(),
-            SpeedUnits::KilometresPerHour => "km/h".to_string(),
-        };
+        // Values the provider does not report, or left out of this step, are
+        // hidden instead of shown as zero
+        let provider = self.config.provider;
+        let mut details = widget::settings::view_section("Details");
+        for (field, label, description, value) in rows {
+            let Some(value) = value.filter(|_| provider.supports(field)) else {
+                continue;
+            };
+            details = details.add(
+                widget::settings::item::builder(label)
+                    .description(description)
+                    .control(widget::text(value)),
+            );
+        }
+        let precipitation_windows = current
+            .map(|current| current.precipitation_windows.as_slice())
+            .unwrap_or_default();
+        for window in precipitation_windows {
+            let value: Vec<String> = [
+                self.config.precipitation_units.format(
+                    window.amount,
+                    window.amount_min,
+                    window.amount_max,
+                ),
+                window
+                    .probability
+                    .map(|probability| format!("{:.0} %", probability.0)),
+            ]
+            .into_iter()
+            .flatten()
+            .collect();
+            if !value.is_empty() {
+                details = details.add(widget::settings::item(
+                    fl!("precipitation-next-hours", hours = window.hours),
+                    widget::text(value.join(", ")),
+                ));
+            }
+        }
 
         let column = widget::column()
             .padding(spacing.space_xs)
@@ -62,10 +176,11 @@ where
             .push(
                 widget::row()
                     .spacing(spacing.space_m)
-                    .push_maybe(data.next_1_hours.as_ref().map(|next_1_hours| {
-                        let symbol = next_1_hours.summary.symbol_code.clone();
-                        widget::icon(WeatherData::icon_handle(symbol)).size(150)
-                    }))
+                    .push_maybe(
+                        current
+                            .and_then(|current| current.symbol)
+                            .map(|symbol| widget::icon(symbol.icon_handle()).size(150)),
+                    )
                     .push(
                         widget::column()
                             .spacing(spacing.space_xs)
@@ -74,87 +189,58 @@ where
                                     .map(widget::text::title4)
                                     .unwrap_or(widget::text::title4("Unknown location")),
                             )
-                            .push_maybe(data.instant.details.air_temperature.map(
-                                |air_temperature| {
-                                    widget::text(format!(
-                                        "{}°",
-                                        self.set_temp_units(air_temperature)
-                                    ))
+                            .push_maybe(
+                                current
+                                    .is_none()
+                                    .then(|| widget::text(fl!("no-current-conditions"))),
+                            )
+                            .push_maybe(
+                                current
+                                    .and_then(|current| current.symbol)
+                                    .map(|symbol| widget::text(symbol.description())),
+                            )
+                            .push_maybe(conditions.air_temperature.map(|air_temperature| {
+                                widget::text(format!("{}°", self.set_temp_units(air_temperature)))
                                     .size(42)
                                     .style(cosmic::style::Text::Accent)
-                                },
-                            )),
+                            })),
                     ),
             )
-            .push(
-                widget::settings::view_section("Details")
-                    .add(widget::settings::item(
-                        "Air Pressure",
-                        widget::text(format!(
-                            "{:.1} {}",
-                            self.calculate_pressure_units(
-                                data.instant
-                                    .details
-                                    .air_pressure_at_sea_level
-                                    .unwrap_or(0.0)
-                            ),
-                            pressure_units
-                        )),
-                    ))
-                    .add(widget::settings::item(
-                        "Cloud Area",
-                        widget::text(format!(
-                            "{} %",
-                            data.instant.details.cloud_area_fraction.unwrap_or(0.0)
-                        )),
-                    ))
-                    .add(widget::settings::item(
-                        "Relative Hummidity",
-                        widget::text(format!(
-                            "{} %",
-                            data.instant.details.relative_humidity.unwrap_or(0.0)
-                        )),
-                    ))
-                    .add(widget::settings::item(
-                        "Wind Direction",
-                        widget::text(format!(
-                            "{} °",
-                            data.instant.details.wind_from_direction.unwrap_or(0.0)
-                        )),
-                    ))
-                    .add(widget::settings::item(
-                        "Wind Speed",
-                        widget::text(format!(
-                            "{:.1} {}",
-                            self.calculate_speed_units(
-                                data.instant.details.wind_speed.unwrap_or(0.0)
-                            ),
-                            speed_units
-                        )),
-                    )),
+            .push_maybe(
+                self.forecast_state()
+                    .map(|forecast_state| self.view_forecast_age(forecast_state, self.timezone())),
             )
-            .push(widget::text(format!("Last updated: {}", last_updated)))
-            .push(widget::text(
-                "Weather data from the Norwegian Meteorological Institute",
-            ));
+            .push_maybe(current.is_some().then_some(details))
+            .push(widget::text(self.config.provider.attribution()));
 
         column.into()
     }
 
-    fn calculate_pressure_units(&self, value: f64) -> f64 {
-        match self.config.pressure_units {
-            PressureUnits::Hectopascal => value,
-            PressureUnits::Bar => value * 0.001_f64,
-            PressureUnits::Kilopascal => value * 0.1_f64,
-            PressureUnits::Psi => value * 0.0145037738_f64,
-        }
+    fn calculate_pressure_units(&self, value: Pressure) -> f64 {
+        self.config.pressure_units.convert(value)
+    }
+
+    fn calculate_speed_units(&self, value: Speed) -> f64 {
+        self.config.speed_units.convert(value)
     }
 
-    fn calculate_speed_units(&self, value: f64) -> f64 {
+    /// Distance in miles alongside speeds in miles per hour, otherwise in
+    /// kilometres.
+    fn format_distance(&self, value: Distance) -> String {
         match self.config.speed_units {
-            SpeedUnits::MetersPerSecond => value,
-            SpeedUnits::MilesPerHour => value / 0.44704_f64,
-            SpeedUnits::KilometresPerHour => value * 3.6,
+            SpeedUnits::MilesPerHour => format!("{:.1} mi", value.miles()),
+            _ => format!("{:.1} km", value.kilometres()),
         }
     }
 }
+
+/// WHO exposure category of a UV index.
+fn uv_index_level(index: f64) -> &'static str {
+    match index.round() as i64 {
+        ..=2 => "low",
+        3..=5 => "moderate",
+        6..=7 => "high",
+        8..=10 => "very-high",
+        _ => "extreme",
+    }
+}
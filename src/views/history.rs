@@ -0,0 +1,91 @@
+use chrono::{DateTime, Local, Utc};
+use cosmic::iced_widget::scrollable::Direction;
+use cosmic::iced_widget::scrollable::Properties;
+use cosmic::prelude::CollectionWidget;
+use cosmic::widget;
+use cosmic::Element;
+
+use crate::app::{App, Message};
+use crate::fl;
+use crate::model::weather::WeatherData;
+
+/// Archived forecasts of the displayed location.
+#[derive(Debug, Default)]
+pub struct History {
+    /// Key of the location the forecasts were archived for.
+    pub key: Option<String>,
+    /// Issue times of the archived forecasts, newest first.
+    pub issue_times: Vec<DateTime<Utc>>,
+    /// Labels of `issue_times` in the dropdown.
+    pub labels: Vec<String>,
+    /// Index of the shown forecast in `issue_times`.
+    pub selected: Option<usize>,
+    pub weather_data: Option<WeatherData>,
+}
+
+impl App
+where
+    Self: cosmic::Application,
+{
+    pub fn view_history(&self) -> Element<Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+        let location = self.config.location().map(|location| location.name.clone());
+
+        if self.config.archive_retention_days == 0 {
+            return widget::text(fl!("history-archive-off")).into();
+        }
+        if self.history.issue_times.is_empty() {
+            return widget::text(fl!("history-empty")).into();
+        }
+
+        let days: Vec<Element<Message>> = self
+            .history
+            .weather_data
+            .iter()
+            .flat_map(|weather_data| &weather_data.daily)
+            .map(|day| self.view_day(day))
+            .collect();
+
+        widget::column()
+            .padding(spacing.space_xs)
+            .spacing(spacing.space_xs)
+            .push(
+                location
+                    .map(widget::text::title4)
+                    .unwrap_or(widget::text::title4("Unknown location")),
+            )
+            .push(
+                widget::settings::view_section(fl!("past-forecasts")).add(
+                    widget::settings::item::builder(fl!("history-issued"))
+                        .description(fl!("history-issued-description"))
+                        .control(widget::dropdown(
+                            &self.history.labels,
+                            self.history.selected,
+                            Message::SelectPastForecast,
+                        )),
+                ),
+            )
+            .push_maybe((!days.is_empty()).then(|| {
+                widget::scrollable(widget::row::with_children(days))
+                    .direction(Direction::Horizontal(Properties::default()))
+            }))
+            .push(widget::text(self.config.provider.attribution()))
+            .into()
+    }
+
+    /// Date and time `issued_at` in the timezone times are shown in, e.g.
+    /// "Sat 1 Jun 14:00".
+    pub fn format_issue_time(&self, issued_at: DateTime<Utc>) -> String {
+        let timezone = self.timezone();
+        let issued_at = issued_at.with_timezone(&Local);
+        let date = match timezone {
+            Some(timezone) => issued_at.with_timezone(&timezone).format("%a %-d %b"),
+            None => issued_at.format("%a %-d %b"),
+        }
+        .to_string();
+        format!(
+            "{date} {}",
+            self.config.timefmt.format_in(&issued_at, timezone).trim()
+        )
+    }
+}
@@ -1,4 +1,5 @@
-use chrono::Local;
+use chrono::{Duration, Local, Utc};
+use chrono_tz::Tz;
 use cosmic::iced::Alignment;
 use cosmic::iced_widget::scrollable::Direction;
 use cosmic::iced_widget::scrollable::Properties;
@@ -6,11 +7,12 @@ use cosmic::prelude::CollectionWidget;
 use cosmic::widget;
 use cosmic::Element;
 
-use crate::app::config::TimeFmt;
-use crate::app::config::Units;
+use crate::app::config::ForecastState;
 use crate::app::{App, Message};
-use crate::model::weather::Timeseries;
-use crate::model::weather::WeatherData;
+use crate::fl;
+use crate::model::quantity::{Percentage, Precipitation, Temperature};
+use crate::model::symbol::WeatherSymbol;
+use crate::model::weather::HourlyForecast;
 
 impl App
 where
@@ -18,60 +20,51 @@ where
 {
     pub fn view_hourly_forecast(&self) -> Element<Message> {
         let current_time = Local::now();
-        let location = self.config.location.clone();
+        let location = self.config.location().map(|location| location.name.clone());
         let spacing = cosmic::theme::active().cosmic().spacing;
-        let Some(weather_data) = &self.config_state.weather_data else {
+        let Some(weather_data) = self.weather_data() else {
             return cosmic::widget::text("No weather data").into();
         };
-        let data = weather_data
-            .properties
-            .timeseries
-            .iter()
-            .min_by_key(|timeseries| (timeseries.time - current_time).num_seconds().abs())
-            .map(|ts| ts.data.clone())
-            .unwrap_or_default();
-
-        let last_updated = match self.config.timefmt {
-            TimeFmt::TwelveHr => weather_data
-                .properties
-                .meta
-                .updated_at
-                .format("%_I:%M %p")
-                .to_string(),
-            TimeFmt::TwentyFourHr => weather_data
-                .properties
-                .meta
-                .updated_at
-                .format("%_H:%M")
-                .to_string(),
-        };
+        let current = weather_data.current_at(current_time);
+        let timezone = self.timezone();
 
-        let timeseries: Vec<Element<Message>> =
-            weather_data
-                .properties
-                .timeseries
-                .iter()
-                .filter(|timeseries| timeseries.time >= current_time)
-                .map(|ts| {
-                    widget::column()
-                        .align_items(Alignment::Center)
-                        .padding(spacing.space_xs)
-                        .spacing(spacing.space_xs)
-                        .push(widget::text(self.format_time(ts)))
-                        .push_maybe(ts.data.next_1_hours.as_ref().map(|next_1_hours| {
-                            let symbol = next_1_hours.summary.symbol_code.clone();
-                            widget::icon(WeatherData::icon_handle(symbol)).size(50)
-                        }))
-                        .push_maybe(ts.data.instant.details.air_temperature.map(
-                            |air_temperature| {
-                                widget::text(format!("{}°", self.set_temp_units(air_temperature)))
-                                    .size(24)
-                                    .style(cosmic::style::Text::Accent)
-                            },
-                        ))
-                        .into()
-                })
-                .collect();
+        let timeseries: Vec<Element<Message>> = weather_data
+            .hourly
+            .iter()
+            .filter(|forecast| forecast.time() >= current_time)
+            .map(|forecast| {
+                widget::column()
+                    .align_items(Alignment::Center)
+                    .padding(spacing.space_xs)
+                    .spacing(spacing.space_xs)
+                    .push(widget::text(self.format_time(forecast, timezone)))
+                    .push_maybe(
+                        forecast
+                            .symbol
+                            .map(|symbol| self.view_symbol_icon(symbol, 50)),
+                    )
+                    .push_maybe(
+                        forecast
+                            .conditions
+                            .air_temperature
+                            .map(|air_temperature| self.view_temperature(air_temperature, 24)),
+                    )
+                    .push_maybe(
+                        self.format_precipitation(
+                            forecast.precipitation_amount,
+                            forecast
+                                .precipitation_window()
+                                .and_then(|window| window.amount_min),
+                            forecast
+                                .precipitation_window()
+                                .and_then(|window| window.amount_max),
+                            forecast.probability_of_precipitation,
+                        )
+                        .map(widget::text::caption),
+                    )
+                    .into()
+            })
+            .collect();
 
         let column = widget::column()
             .padding(spacing.space_xs)
@@ -79,10 +72,11 @@ where
             .push(
                 widget::row()
                     .spacing(spacing.space_m)
-                    .push_maybe(data.next_1_hours.as_ref().map(|next_1_hours| {
-                        let symbol = next_1_hours.summary.symbol_code.clone();
-                        widget::icon(WeatherData::icon_handle(symbol)).size(150)
-                    }))
+                    .push_maybe(
+                        current
+                            .and_then(|current| current.symbol)
+                            .map(|symbol| widget::icon(symbol.icon_handle()).size(150)),
+                    )
                     .push(
                         widget::column()
                             .spacing(spacing.space_xs)
@@ -91,41 +85,142 @@ where
                                     .map(widget::text::title4)
                                     .unwrap_or(widget::text::title4("Unknown location")),
                             )
-                            .push_maybe(data.instant.details.air_temperature.map(
-                                |air_temperature| {
-                                    widget::text(format!(
-                                        "{}°",
-                                        self.set_temp_units(air_temperature)
-                                    ))
-                                    .size(42)
-                                    .style(cosmic::style::Text::Accent)
-                                },
-                            )),
+                            .push_maybe(
+                                current
+                                    .is_none()
+                                    .then(|| widget::text(fl!("no-current-conditions"))),
+                            )
+                            .push_maybe(
+                                current
+                                    .and_then(|current| current.symbol)
+                                    .map(|symbol| widget::text(symbol.description())),
+                            )
+                            .push_maybe(
+                                current
+                                    .and_then(|current| current.conditions.air_temperature)
+                                    .map(|air_temperature| {
+                                        widget::text(format!(
+                                            "{}°",
+                                            self.set_temp_units(air_temperature)
+                                        ))
+                                        .size(42)
+                                        .style(cosmic::style::Text::Accent)
+                                    }),
+                            ),
                     ),
             )
+            .push_maybe(
+                self.forecast_state()
+                    .map(|forecast_state| self.view_forecast_age(forecast_state, timezone)),
+            )
+            .push(self.view_hourly_chart(weather_data, timezone))
             .push(
                 widget::scrollable(widget::row::with_children(timeseries))
                     .direction(Direction::Horizontal(Properties::default())),
             )
-            .push(widget::text(format!("Last updated: {}", last_updated)))
-            .push(widget::text(
-                "Weather data from the Norwegian Meteorological Institute",
-            ));
+            .push(widget::text(self.config.provider.attribution()));
 
         column.into()
     }
 
-    pub fn set_temp_units(&self, temp: f64) -> i64 {
-        match self.config.units {
-            Units::Fahrenheit => ((temp * (9_f64 / 5_f64)) + 32_f64) as i64,
-            Units::Celsius => temp as i64,
+    pub fn view_symbol_icon<'a>(&self, symbol: WeatherSymbol, size: u16) -> Element<'a, Message> {
+        widget::icon(symbol.icon_handle()).size(size).into()
+    }
+
+    pub fn view_temperature<'a>(
+        &self,
+        temperature: Temperature,
+        size: u16,
+    ) -> Element<'a, Message> {
+        widget::text(format!("{}°", self.set_temp_units(temperature)))
+            .size(size)
+            .style(cosmic::style::Text::Accent)
+            .into()
+    }
+
+    pub fn set_temp_units(&self, temp: Temperature) -> i64 {
+        self.config.units.convert(temp) as i64
+    }
+
+    /// Expected precipitation and its probability, e.g. "0.2–1.4 mm, 40 %".
+    ///
+    /// `None` if no precipitation is expected, so dry hours stay uncluttered.
+    pub fn format_precipitation(
+        &self,
+        amount: Option<Precipitation>,
+        min: Option<Precipitation>,
+        max: Option<Precipitation>,
+        probability: Option<Percentage>,
+    ) -> Option<String> {
+        let expected = [amount, max]
+            .into_iter()
+            .flatten()
+            .any(|amount| amount.0 > 0.0)
+            || probability.is_some_and(|probability| probability.0 > 0.0);
+        if !expected {
+            return None;
         }
+        let parts: Vec<String> = [
+            self.config.precipitation_units.format(amount, min, max),
+            probability.map(|probability| format!("{:.0} %", probability.0)),
+        ]
+        .into_iter()
+        .flatten()
+        .collect();
+        Some(parts.join(", "))
+    }
+
+    /// Start of `forecast` in `timezone`, or in the system timezone if `None`.
+    pub fn format_time(&self, forecast: &HourlyForecast, timezone: Option<Tz>) -> String {
+        self.config.timefmt.format_in(&forecast.time(), timezone)
     }
 
-    fn format_time(&self, ts: &Timeseries) -> String {
-        match self.config.timefmt {
-            TimeFmt::TwelveHr => ts.time.format("%_I:%M %p").to_string(),
-            TimeFmt::TwentyFourHr => ts.time.format("%_H:%M").to_string(),
+    /// Update time of the forecast in `timezone`, and how old it is once it
+    /// has expired and cannot be refreshed.
+    pub fn view_forecast_age<'a>(
+        &self,
+        forecast_state: &ForecastState,
+        timezone: Option<Tz>,
+    ) -> Element<'a, Message> {
+        let spacing = cosmic::theme::active().cosmic().spacing;
+        let mut column = widget::column().spacing(spacing.space_xxs);
+
+        if let Some(weather_data) = &forecast_state.weather_data {
+            let time = self
+                .config
+                .timefmt
+                .format_in(&weather_data.updated_at, timezone);
+            column = column.push(widget::text(fl!("last-updated", time = time.trim())));
+        }
+
+        // Until a refresh failed the expired forecast is about to be replaced
+        if forecast_state.is_expired() && (self.config.offline || forecast_state.refresh_failed) {
+            let age = forecast_state
+                .last_request
+                .map(|last_request| format_age(Utc::now() - last_request.to_utc()))
+                .unwrap_or_default();
+            let outdated = if self.config.offline {
+                fl!("offline-outdated", age = age)
+            } else {
+                fl!("outdated", age = age)
+            };
+            column =
+                column.push(widget::text::heading(outdated).style(cosmic::style::Text::Accent));
         }
+
+        column.into()
+    }
+}
+
+/// Localized age, e.g. "3 hours ago".
+pub fn format_age(age: Duration) -> String {
+    if age.num_days() > 0 {
+        fl!("age-days", count = age.num_days())
+    } else if age.num_hours() > 0 {
+        fl!("age-hours", count = age.num_hours())
+    } else if age.num_minutes() > 0 {
+        fl!("age-minutes", count = age.num_minutes())
+    } else {
+        fl!("age-just-now")
     }
 }
@@ -1,3 +1,6 @@
+pub mod chart;
+pub mod compare;
 pub mod daily;
 pub mod detail;
+pub mod history;
 pub mod hourly;